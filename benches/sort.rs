@@ -1,4 +1,7 @@
+use algorithms_and_datastructures::random::Rng;
 use algorithms_and_datastructures::sorting_algorithms as algos;
+use algos::parallel_sort::Parallelism;
+use algos::quick_sort::PivotStrategy;
 use criterion::{black_box, criterion_group, criterion_main, Bencher, BenchmarkId, Criterion};
 
 pub fn already_sorted(c: &mut Criterion) {
@@ -20,8 +23,144 @@ pub fn already_sorted(c: &mut Criterion) {
         group.bench_with_input(BenchmarkId::new("HeapSort", size), &size, |b, &size| {
             b.iter(|| algos::heap_sort::heap_sort(&mut sequence[0..size]));
         });
+
+        group.bench_with_input(BenchmarkId::new("TimSort", size), &size, |b, &size| {
+            b.iter(|| algos::tim_sort::tim_sort(&mut sequence[0..size]));
+        });
+
+        group.bench_with_input(BenchmarkId::new("BubbleSort", size), &size, |b, &size| {
+            b.iter(|| algos::bubble_sort::bubble_sort(&mut sequence[0..size]));
+        });
+
+        group.bench_with_input(
+            BenchmarkId::new("InsertionSort", size),
+            &size,
+            |b, &size| {
+                b.iter(|| algos::insertion_sort::insertion_sort(&mut sequence[0..size]));
+            },
+        );
+    }
+}
+
+/// Pseudo-random sequence from the crate's generator, the same for every run.
+fn shuffled(size: usize) -> Vec<i64> {
+    let mut rng = Rng::new(41);
+
+    (0..size).map(|_| (rng.next_u64() >> 33) as i64).collect()
+}
+
+pub fn quadratic(c: &mut Criterion) {
+    let max_size = 10_000;
+    let sequence = shuffled(max_size);
+
+    let mut group = c.benchmark_group("Small Shuffled Sequence");
+    group.sample_size(10);
+
+    for size in (1_000..=max_size).step_by(1_000) {
+        let mut run = |name: &str, sort: fn(&mut [i64])| {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                b.iter(|| sort(&mut sequence[0..size].to_vec()));
+            });
+        };
+
+        run("BubbleSort", algos::bubble_sort::bubble_sort);
+        run("SelectionSort", algos::selection_sort::selection_sort);
+        run("InsertionSort", algos::insertion_sort::insertion_sort);
+        run("QuickSort", algos::quick_sort::quick_sort);
+    }
+}
+
+pub fn unstable(c: &mut Criterion) {
+    let max_size = 1_000_000;
+    let sequence = shuffled(max_size);
+    let duplicates: Vec<i64> = sequence.iter().map(|v| v % 16).collect();
+
+    for (title, sequence) in [
+        ("Shuffled Sequence", &sequence),
+        ("Many Duplicates", &duplicates),
+    ] {
+        let mut group = c.benchmark_group(title);
+        group.sample_size(10);
+
+        for size in (100_000..=max_size).step_by(300_000) {
+            let mut run = |name: &str, sort: fn(&mut [i64])| {
+                group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                    b.iter(|| sort(&mut sequence[0..size].to_vec()));
+                });
+            };
+
+            run("IntroSort", algos::intro_sort::intro_sort);
+            run("TimSort", algos::tim_sort::tim_sort);
+            run("QuickSort", algos::quick_sort::quick_sort);
+            run("SortUnstable", <[i64]>::sort_unstable);
+        }
+    }
+}
+
+/// Best, average and worst cases of quick sort with every pivot strategy.
+pub fn pivot_strategies(c: &mut Criterion) {
+    let size = 20_000;
+    let sorted: Vec<i64> = (0..size as i64).collect();
+    let shuffled = shuffled(size);
+
+    let strategies = [
+        PivotStrategy::First,
+        PivotStrategy::Last,
+        PivotStrategy::Middle,
+        PivotStrategy::Random(42),
+        PivotStrategy::MedianOfThree,
+        PivotStrategy::Ninther,
+        PivotStrategy::MedianOfMedians,
+    ];
+
+    for (title, sequence) in [
+        ("Pivot Strategies Sorted", &sorted),
+        ("Pivot Strategies Shuffled", &shuffled),
+    ] {
+        let mut group = c.benchmark_group(title);
+        group.sample_size(10);
+
+        for strategy in strategies {
+            group.bench_function(format!("{:?}", strategy), |b| {
+                b.iter(|| {
+                    algos::quick_sort::quick_sort_with_pivot(&mut sequence.clone(), strategy)
+                });
+            });
+        }
+    }
+}
+
+pub fn parallel(c: &mut Criterion) {
+    let max_size = 10_000_000;
+    let sequence = shuffled(max_size);
+
+    let mut group = c.benchmark_group("Parallel Shuffled Sequence");
+    group.sample_size(10);
+
+    for size in (2_000_000..=max_size).step_by(4_000_000) {
+        let mut run = |name: &str, sort: &dyn Fn(&mut [i64])| {
+            group.bench_with_input(BenchmarkId::new(name, size), &size, |b, &size| {
+                b.iter(|| sort(&mut sequence[0..size].to_vec()));
+            });
+        };
+
+        run("MergeSort", &algos::merge_sort::merge_sort);
+        run("ParMergeSort", &|seq| {
+            algos::parallel_sort::par_merge_sort(seq, Parallelism::default())
+        });
+        run("QuickSort", &algos::quick_sort::quick_sort);
+        run("ParQuickSort", &|seq| {
+            algos::parallel_sort::par_quick_sort(seq, Parallelism::default())
+        });
     }
 }
 
-criterion_group!(benches, already_sorted);
+criterion_group!(
+    benches,
+    already_sorted,
+    quadratic,
+    unstable,
+    pivot_strategies,
+    parallel
+);
 criterion_main!(benches);
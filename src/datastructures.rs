@@ -1,3 +1,27 @@
+pub mod avl_tree;
+pub mod bit_set;
+pub mod bit_vector;
+pub mod bloom_filter;
+pub mod cache;
+pub mod count_min_sketch;
+pub mod deque;
+pub mod fenwick_tree;
+pub mod hash_map;
+pub mod hyper_log_log;
+pub mod interval_tree;
+pub mod kd_tree;
+pub mod linked_list;
+pub mod min_max_heap;
+pub mod persistent;
+pub mod queue;
+pub mod radix_tree;
+pub mod segment_tree;
+pub mod sliding_window;
+pub mod sparse_table;
+pub mod stack;
+pub mod trie;
+pub mod wavelet_matrix;
+
 pub mod heap {
     use std::cmp::Ordering;
 
@@ -26,6 +50,24 @@ pub mod heap {
                 Some(greatest)
             }
         }
+
+        pub fn peek(&self) -> Option<&V> {
+            self.seq.first()
+        }
+
+        pub fn insert(&mut self, value: V) {
+            self.seq.push(value);
+            let start = self.seq.len() - 1;
+            sift_up(&mut self.seq, |a, b| a.cmp(b), start);
+        }
+
+        pub fn len(&self) -> usize {
+            self.seq.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.seq.is_empty()
+        }
     }
 
     pub struct MinHeap<V: Ord> {
@@ -61,6 +103,14 @@ pub mod heap {
             let start = self.seq.len() - 1;
             sift_up(&mut self.seq, |a, b| b.cmp(a), start);
         }
+
+        pub fn len(&self) -> usize {
+            self.seq.len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.seq.is_empty()
+        }
     }
 
     pub fn heapify_min<V: Ord>(seq: &mut [V]) {
@@ -72,14 +122,17 @@ pub mod heap {
     }
 
     /// Heapify the sequence. The greater value (using the cmp function) is on top.
-    pub fn heapify<V: Ord>(seq: &mut [V], cmp: impl Fn(&V, &V) -> Ordering) {
-        for idx in (0..seq.len()).rev() {
-            sift_down(seq, &cmp, idx);
+    ///
+    /// Floyd's bottom-up construction: the leaves are already heaps, so the inner nodes are sifted down from the
+    /// last one. Most nodes are near the bottom and sift down few levels, hence it takes O(n).
+    pub fn heapify<V>(seq: &mut [V], mut cmp: impl FnMut(&V, &V) -> Ordering) {
+        for idx in (0..seq.len() / 2).rev() {
+            sift_down(seq, &mut cmp, idx);
         }
     }
 
     /// Perform a sift up operation from given start index. Used in `insert`
-    pub fn sift_up<V: Ord>(seq: &mut [V], cmp: impl Fn(&V, &V) -> Ordering, start: usize) {
+    pub fn sift_up<V>(seq: &mut [V], mut cmp: impl FnMut(&V, &V) -> Ordering, start: usize) {
         let mut cursor = start;
 
         while cursor > 0 {
@@ -94,7 +147,7 @@ pub mod heap {
         }
     }
 
-    pub fn sift_down<V: Ord>(seq: &mut [V], cmp: impl Fn(&V, &V) -> Ordering, start: usize) {
+    pub fn sift_down<V>(seq: &mut [V], mut cmp: impl FnMut(&V, &V) -> Ordering, start: usize) {
         let lc = |i| 2 * i + 1;
         let rc = |i| 2 * i + 2;
         let mut cursor = start;
@@ -125,9 +178,46 @@ pub mod heap {
         }
     }
 
+    /// Same as `sift_down`, with Floyd's bottom-up optimization for elements that likely belong near the leaves,
+    /// like the last one moved to the top when extracting.
+    ///
+    /// Instead of comparing the element with both children at every level, it follows the greatest children down
+    /// to a leaf with one comparison per level, climbs back up to the place of the element and shifts the path
+    /// above it up by one level. It performs about half the comparisons of `sift_down` in that case.
+    pub fn sift_down_bottom_up<V>(
+        seq: &mut [V],
+        mut cmp: impl FnMut(&V, &V) -> Ordering,
+        start: usize,
+    ) {
+        let mut cursor = start;
+
+        // path of the greatest children
+        while 2 * cursor + 1 < seq.len() {
+            let lc_idx = 2 * cursor + 1;
+            let rc_idx = lc_idx + 1;
+
+            cursor = if rc_idx < seq.len() && cmp(&seq[rc_idx], &seq[lc_idx]) == Ordering::Greater {
+                rc_idx
+            } else {
+                lc_idx
+            };
+        }
+
+        // the element goes below the last one on the path that is not smaller
+        while cursor > start && cmp(&seq[start], &seq[cursor]) == Ordering::Greater {
+            cursor = (cursor - 1) / 2;
+        }
+
+        // every swap with the top moves its element to `cursor` and the previous one of the path to the top
+        while cursor > start {
+            seq.swap(start, cursor);
+            cursor = (cursor - 1) / 2;
+        }
+    }
+
     #[cfg(test)]
     mod tests {
-        use super::{MaxHeap, MinHeap};
+        use super::{heapify_max, sift_down, sift_down_bottom_up, MaxHeap, MinHeap};
 
         #[test]
         fn heap() {
@@ -164,5 +254,24 @@ pub mod heap {
                 assert_eq!(Some(i), min_heap.extract());
             }
         }
+
+        #[test]
+        fn bottom_up() {
+            let mut seq: Vec<u32> = (0..200).map(|i| (i * 37) % 101).collect();
+            heapify_max(&mut seq);
+
+            for end in (1..seq.len()).rev() {
+                let mut expected = seq[..end].to_vec();
+                expected[0] = seq[end];
+                sift_down(&mut expected, |a, b| a.cmp(b), 0);
+
+                seq.swap(0, end);
+                sift_down_bottom_up(&mut seq[..end], |a, b| a.cmp(b), 0);
+
+                // the same element ends on top, both keep the heap property
+                assert_eq!(expected[0], seq[0]);
+                assert!((1..end).all(|i| seq[(i - 1) / 2] >= seq[i]));
+            }
+        }
     }
 }
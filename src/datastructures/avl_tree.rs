@@ -0,0 +1,461 @@
+//! AVL tree: a binary search tree where the heights of the two subtrees of every node differ by at most one.
+//!
+//! After an insertion or a removal the nodes on the path back to the root are rebalanced with rotations, which
+//! keeps the height below `1.44 log2(n + 2)`, so all the operations are O(log n).
+//!
+//! The tree can be augmented: every node stores a summary of its subtree computed from its key and the summaries
+//! of its children (see `Augment`), kept up to date through the rotations. Searches can then use `root` to walk
+//! down the tree and skip whole subtrees thanks to the summaries, like `IntervalTree` does.
+
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+/// Summary of a subtree stored in every node, e.g. its size or the maximum of some value.
+pub trait Augment<K> {
+    type Summary;
+
+    fn summarize(
+        key: &K,
+        left: Option<&Self::Summary>,
+        right: Option<&Self::Summary>,
+    ) -> Self::Summary;
+}
+
+/// No augmentation.
+impl<K> Augment<K> for () {
+    type Summary = ();
+
+    fn summarize(_: &K, _: Option<&()>, _: Option<&()>) {}
+}
+
+type Tree<K, V, S> = Box<Node<K, V, S>>;
+type Link<K, V, S> = Option<Tree<K, V, S>>;
+
+pub struct Node<K, V, S> {
+    key: K,
+    value: V,
+    height: usize,
+    summary: S,
+    left: Link<K, V, S>,
+    right: Link<K, V, S>,
+}
+
+impl<K, V, S> Node<K, V, S> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn value(&self) -> &V {
+        &self.value
+    }
+
+    /// Summary of the subtree rooted at this node.
+    pub fn summary(&self) -> &S {
+        &self.summary
+    }
+
+    pub fn left(&self) -> Option<&Node<K, V, S>> {
+        self.left.as_deref()
+    }
+
+    pub fn right(&self) -> Option<&Node<K, V, S>> {
+        self.right.as_deref()
+    }
+}
+
+fn height<K, V, S>(link: &Link<K, V, S>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+/// Recomputes the height and summary of `node` from its children.
+fn update<K, V, A: Augment<K>>(node: &mut Node<K, V, A::Summary>) {
+    node.height = 1 + height(&node.left).max(height(&node.right));
+    node.summary = A::summarize(
+        &node.key,
+        node.left.as_ref().map(|left| &left.summary),
+        node.right.as_ref().map(|right| &right.summary),
+    );
+}
+
+fn rotate_right<K, V, A: Augment<K>>(mut node: Tree<K, V, A::Summary>) -> Tree<K, V, A::Summary> {
+    let mut left = node.left.take().expect("rotating without a left child");
+    node.left = left.right.take();
+    update::<K, V, A>(&mut node);
+    left.right = Some(node);
+    update::<K, V, A>(&mut left);
+    left
+}
+
+fn rotate_left<K, V, A: Augment<K>>(mut node: Tree<K, V, A::Summary>) -> Tree<K, V, A::Summary> {
+    let mut right = node.right.take().expect("rotating without a right child");
+    node.right = right.left.take();
+    update::<K, V, A>(&mut node);
+    right.left = Some(node);
+    update::<K, V, A>(&mut right);
+    right
+}
+
+/// Restores the balance of `node`, whose subtrees are balanced and differ in height by at most 2.
+fn balance<K, V, A: Augment<K>>(mut node: Tree<K, V, A::Summary>) -> Tree<K, V, A::Summary> {
+    update::<K, V, A>(&mut node);
+
+    let (left, right) = (height(&node.left), height(&node.right));
+
+    if left > right + 1 {
+        let child = node.left.take().unwrap();
+        // a left-right case becomes a left-left case
+        node.left = Some(if height(&child.left) < height(&child.right) {
+            rotate_left::<K, V, A>(child)
+        } else {
+            child
+        });
+        rotate_right::<K, V, A>(node)
+    } else if right > left + 1 {
+        let child = node.right.take().unwrap();
+        node.right = Some(if height(&child.right) < height(&child.left) {
+            rotate_right::<K, V, A>(child)
+        } else {
+            child
+        });
+        rotate_left::<K, V, A>(node)
+    } else {
+        node
+    }
+}
+
+pub struct AvlTree<K, V, A: Augment<K> = ()> {
+    root: Link<K, V, A::Summary>,
+    len: usize,
+    _augment: PhantomData<A>,
+}
+
+impl<K: Ord, V, A: Augment<K>> Default for AvlTree<K, V, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V, A: Augment<K>> AvlTree<K, V, A> {
+    pub fn new() -> Self {
+        Self {
+            root: None,
+            len: 0,
+            _augment: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Height of the tree, 0 if it's empty.
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Root node, to implement searches guided by the summaries.
+    pub fn root(&self) -> Option<&Node<K, V, A::Summary>> {
+        self.root.as_deref()
+    }
+
+    /// Inserts `value` under `key`, returning the previous value if the key was present.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (root, old) = Self::insert_at(self.root.take(), key, value);
+        self.root = Some(root);
+
+        if old.is_none() {
+            self.len += 1;
+        }
+
+        old
+    }
+
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        Self::remove_at(&mut self.root, key).map(|(_, value)| {
+            self.len -= 1;
+            value
+        })
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut node = self.root.as_deref();
+
+        while let Some(n) = node {
+            node = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_deref(),
+                Ordering::Greater => n.right.as_deref(),
+                Ordering::Equal => return Some(&n.value),
+            };
+        }
+
+        None
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut node = self.root.as_deref_mut();
+
+        while let Some(n) = node {
+            node = match key.cmp(&n.key) {
+                Ordering::Less => n.left.as_deref_mut(),
+                Ordering::Greater => n.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut n.value),
+            };
+        }
+
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+
+        Some((&node.key, &node.value))
+    }
+
+    /// Entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+
+        Some((&node.key, &node.value))
+    }
+
+    /// Iterates over the entries in key order.
+    pub fn iter(&self) -> Iter<'_, K, V, A::Summary> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+        };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    fn insert_at(
+        link: Link<K, V, A::Summary>,
+        key: K,
+        value: V,
+    ) -> (Tree<K, V, A::Summary>, Option<V>) {
+        let mut node = match link {
+            None => {
+                let node = Node {
+                    summary: A::summarize(&key, None, None),
+                    key,
+                    value,
+                    height: 1,
+                    left: None,
+                    right: None,
+                };
+                return (Box::new(node), None);
+            }
+            Some(node) => node,
+        };
+
+        let old = match key.cmp(&node.key) {
+            Ordering::Less => {
+                let (left, old) = Self::insert_at(node.left.take(), key, value);
+                node.left = Some(left);
+                old
+            }
+            Ordering::Greater => {
+                let (right, old) = Self::insert_at(node.right.take(), key, value);
+                node.right = Some(right);
+                old
+            }
+            Ordering::Equal => {
+                let old = std::mem::replace(&mut node.value, value);
+                return (node, Some(old));
+            }
+        };
+
+        (balance::<K, V, A>(node), old)
+    }
+
+    /// Removes `key` from the subtree at `link`, rebalancing it.
+    fn remove_at(link: &mut Link<K, V, A::Summary>, key: &K) -> Option<(K, V)> {
+        let mut node = link.take()?;
+
+        let removed = match key.cmp(&node.key) {
+            Ordering::Less => Self::remove_at(&mut node.left, key),
+            Ordering::Greater => Self::remove_at(&mut node.right, key),
+            Ordering::Equal => {
+                let node = *node;
+
+                *link = match (node.left, node.right) {
+                    (None, child) | (child, None) => child,
+                    (left, right) => {
+                        // the successor takes the place of the removed node
+                        let mut right = right;
+                        let mut successor = Self::remove_min(&mut right);
+                        successor.left = left;
+                        successor.right = right;
+                        Some(balance::<K, V, A>(successor))
+                    }
+                };
+
+                return Some((node.key, node.value));
+            }
+        };
+
+        *link = Some(balance::<K, V, A>(node));
+        removed
+    }
+
+    /// Detaches the smallest node of the non-empty subtree at `link`.
+    fn remove_min(link: &mut Link<K, V, A::Summary>) -> Tree<K, V, A::Summary> {
+        let mut node = link.take().expect("empty subtree");
+
+        if node.left.is_none() {
+            *link = node.right.take();
+            node
+        } else {
+            let min = Self::remove_min(&mut node.left);
+            *link = Some(balance::<K, V, A>(node));
+            min
+        }
+    }
+}
+
+/// In-order iterator over the entries of an `AvlTree`.
+pub struct Iter<'a, K, V, S> {
+    /// Nodes whose left subtree has been visited, the next one on top.
+    stack: Vec<&'a Node<K, V, S>>,
+    len: usize,
+}
+
+impl<'a, K, V, S> Iter<'a, K, V, S> {
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V, S>>) {
+        while let Some(n) = node {
+            self.stack.push(n);
+            node = n.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V, S> Iterator for Iter<'a, K, V, S> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        self.len -= 1;
+
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V, S> ExactSizeIterator for Iter<'_, K, V, S> {}
+
+impl<'a, K: Ord, V, A: Augment<K>> IntoIterator for &'a AvlTree<K, V, A> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V, A::Summary>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V, A: Augment<K>> FromIterator<(K, V)> for AvlTree<K, V, A> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{Augment, AvlTree, Node};
+    use crate::random::Rng;
+
+    /// Subtree sizes, to check that the summaries survive the rotations.
+    struct Size;
+
+    impl<K> Augment<K> for Size {
+        type Summary = usize;
+
+        fn summarize(_: &K, left: Option<&usize>, right: Option<&usize>) -> usize {
+            1 + left.unwrap_or(&0) + right.unwrap_or(&0)
+        }
+    }
+
+    /// Checks the AVL and augmentation invariants, returning the height and size of the subtree.
+    fn check(node: Option<&Node<u32, u32, usize>>) -> (usize, usize) {
+        match node {
+            None => (0, 0),
+            Some(node) => {
+                let (left_height, left_size) = check(node.left());
+                let (right_height, right_size) = check(node.right());
+
+                assert!(left_height.abs_diff(right_height) <= 1);
+                assert_eq!(1 + left_size + right_size, *node.summary());
+
+                (
+                    1 + left_height.max(right_height),
+                    1 + left_size + right_size,
+                )
+            }
+        }
+    }
+
+    #[test]
+    fn against_btree_map() {
+        let mut rng = Rng::new(34);
+        let mut tree = AvlTree::<u32, u32, Size>::new();
+        let mut reference = BTreeMap::new();
+
+        for i in 0..2000u32 {
+            let key = rng.below(1000) as u32;
+            assert_eq!(reference.insert(key, i), tree.insert(key, i));
+
+            if i % 3 == 0 {
+                let key = rng.below(1000) as u32;
+                assert_eq!(reference.remove(&key), tree.remove(&key));
+            }
+        }
+
+        assert_eq!(reference.len(), tree.len());
+        assert_eq!((tree.height(), tree.len()), check(tree.root()));
+        assert!(tree.height() as f64 <= 1.44 * (tree.len() as f64 + 2.0).log2());
+
+        assert!(tree.iter().eq(reference.iter()));
+        assert_eq!(reference.first_key_value(), tree.first());
+        assert_eq!(reference.last_key_value(), tree.last());
+
+        *tree.get_mut(&7).unwrap() = 42;
+        assert_eq!(Some(&42), tree.get(&7));
+        assert_eq!(None, tree.get(&1000));
+    }
+
+    #[test]
+    fn sorted_insertions_stay_balanced() {
+        let tree: AvlTree<u32, ()> = (0..1023).map(|i| (i, ())).collect();
+
+        assert_eq!(10, tree.height());
+    }
+}
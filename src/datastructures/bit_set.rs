@@ -0,0 +1,449 @@
+//! Sets of small integers stored as bits, 64 per word.
+//!
+//! Membership tests and updates are a shift and a mask, the set operations work on whole words, and iterating
+//! over the elements jumps from one set bit to the next with `trailing_zeros`, so it takes time proportional to
+//! the number of words plus the number of elements.
+//!
+//! - `FixedBitSet`: a subset of `0..capacity` for a capacity fixed on creation, e.g. the visited vertices of
+//!   a graph traversal. It supports the complement.
+//! - `BitSet`: grows as needed to hold the largest element.
+
+use std::iter::FromIterator;
+
+const WORD_BITS: usize = 64;
+
+fn word_count(bits: usize) -> usize {
+    bits.div_ceil(WORD_BITS)
+}
+
+/// Growable set of integers.
+#[derive(Debug, Clone, Default)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new() -> Self {
+        Self { words: Vec::new() }
+    }
+
+    /// Creates a set that can hold `0..bits` without growing.
+    pub fn with_capacity(bits: usize) -> Self {
+        Self {
+            words: vec![0; word_count(bits)],
+        }
+    }
+
+    /// Number of elements that fit without growing.
+    pub fn capacity(&self) -> usize {
+        self.words.len() * WORD_BITS
+    }
+
+    /// Number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Adds `value` to the set, returning `false` if it was already present.
+    pub fn insert(&mut self, value: usize) -> bool {
+        let word = value / WORD_BITS;
+
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+
+        let mask = 1 << (value % WORD_BITS);
+        let absent = self.words[word] & mask == 0;
+        self.words[word] |= mask;
+
+        absent
+    }
+
+    /// Removes `value` from the set, returning `false` if it was not present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let present = self.contains(value);
+
+        if present {
+            self.words[value / WORD_BITS] &= !(1 << (value % WORD_BITS));
+        }
+
+        present
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.words
+            .get(value / WORD_BITS)
+            .is_some_and(|w| w & (1 << (value % WORD_BITS)) != 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// Iterates over the elements in increasing order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(&self.words)
+    }
+
+    /// The underlying words, the element `i` is the bit `i % 64` of the word `i / 64`.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn union_with(&mut self, other: &BitSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word |= other;
+        }
+    }
+
+    pub fn intersect_with(&mut self, other: &BitSet) {
+        for (i, word) in self.words.iter_mut().enumerate() {
+            *word &= other.words.get(i).copied().unwrap_or(0);
+        }
+    }
+
+    pub fn difference_with(&mut self, other: &BitSet) {
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word &= !other;
+        }
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &BitSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word ^= other;
+        }
+    }
+
+    pub fn is_subset(&self, other: &BitSet) -> bool {
+        self.words
+            .iter()
+            .enumerate()
+            .all(|(i, w)| w & !other.words.get(i).copied().unwrap_or(0) == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &BitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+}
+
+/// Sets are equal if they have the same elements, whatever their capacity.
+impl PartialEq for BitSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_subset(other) && other.is_subset(self)
+    }
+}
+
+impl Eq for BitSet {}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut set = Self::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<usize> for BitSet {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a BitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Subset of `0..capacity`, for a capacity fixed on creation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedBitSet {
+    words: Vec<u64>,
+    capacity: usize,
+}
+
+impl FixedBitSet {
+    /// Creates an empty subset of `0..capacity`.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            words: vec![0; word_count(capacity)],
+            capacity,
+        }
+    }
+
+    /// Creates the full set `0..capacity`.
+    pub fn full(capacity: usize) -> Self {
+        let mut set = Self::new(capacity);
+        set.complement();
+        set
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of elements in the set.
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// Adds `value` to the set, returning `false` if it was already present.
+    ///
+    /// Panics if `value` is not below the capacity.
+    pub fn insert(&mut self, value: usize) -> bool {
+        self.check(value);
+
+        let mask = 1 << (value % WORD_BITS);
+        let word = &mut self.words[value / WORD_BITS];
+        let absent = *word & mask == 0;
+        *word |= mask;
+
+        absent
+    }
+
+    /// Removes `value` from the set, returning `false` if it was not present.
+    pub fn remove(&mut self, value: usize) -> bool {
+        let present = self.contains(value);
+
+        if present {
+            self.words[value / WORD_BITS] &= !(1 << (value % WORD_BITS));
+        }
+
+        present
+    }
+
+    /// Sets the presence of `value`.
+    ///
+    /// Panics if `value` is not below the capacity.
+    pub fn set(&mut self, value: usize, present: bool) {
+        if present {
+            self.insert(value);
+        } else {
+            self.check(value);
+            self.remove(value);
+        }
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        value < self.capacity && self.words[value / WORD_BITS] & (1 << (value % WORD_BITS)) != 0
+    }
+
+    pub fn clear(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// Replaces the set with `0..capacity` minus the set.
+    pub fn complement(&mut self) {
+        self.words.iter_mut().for_each(|w| *w = !*w);
+
+        // the bits past the capacity must stay clear
+        let rest = self.capacity % WORD_BITS;
+
+        if let (true, Some(last)) = (rest > 0, self.words.last_mut()) {
+            *last &= (1 << rest) - 1;
+        }
+    }
+
+    /// Iterates over the elements in increasing order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(&self.words)
+    }
+
+    /// The underlying words, the element `i` is the bit `i % 64` of the word `i / 64`.
+    pub fn as_words(&self) -> &[u64] {
+        &self.words
+    }
+
+    pub fn union_with(&mut self, other: &FixedBitSet) {
+        self.zip_words(other, |a, b| a | b);
+    }
+
+    pub fn intersect_with(&mut self, other: &FixedBitSet) {
+        self.zip_words(other, |a, b| a & b);
+    }
+
+    pub fn difference_with(&mut self, other: &FixedBitSet) {
+        self.zip_words(other, |a, b| a & !b);
+    }
+
+    pub fn symmetric_difference_with(&mut self, other: &FixedBitSet) {
+        self.zip_words(other, |a, b| a ^ b);
+    }
+
+    pub fn is_subset(&self, other: &FixedBitSet) -> bool {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .all(|(a, b)| a & !b == 0)
+            && self.words[other.words.len().min(self.words.len())..]
+                .iter()
+                .all(|&w| w == 0)
+    }
+
+    pub fn is_disjoint(&self, other: &FixedBitSet) -> bool {
+        self.words.iter().zip(&other.words).all(|(a, b)| a & b == 0)
+    }
+
+    fn check(&self, value: usize) {
+        assert!(
+            value < self.capacity,
+            "{} is out of the set capacity {}",
+            value,
+            self.capacity
+        );
+    }
+
+    fn zip_words(&mut self, other: &FixedBitSet, op: impl Fn(u64, u64) -> u64) {
+        assert_eq!(
+            self.capacity, other.capacity,
+            "sets of different capacities"
+        );
+
+        for (word, &other) in self.words.iter_mut().zip(&other.words) {
+            *word = op(*word, other);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a FixedBitSet {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the set bits of a slice of words.
+pub struct Iter<'a> {
+    words: &'a [u64],
+    /// Index of `current` in `words`.
+    idx: usize,
+    /// Bits of the current word not returned yet.
+    current: u64,
+}
+
+impl<'a> Iter<'a> {
+    fn new(words: &'a [u64]) -> Self {
+        Self {
+            words,
+            idx: 0,
+            current: words.first().copied().unwrap_or(0),
+        }
+    }
+}
+
+impl Iterator for Iter<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.current == 0 {
+            self.idx += 1;
+            self.current = *self.words.get(self.idx)?;
+        }
+
+        let bit = self.current.trailing_zeros() as usize;
+        // clear the lowest set bit
+        self.current &= self.current - 1;
+
+        Some(self.idx * WORD_BITS + bit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BitSet, FixedBitSet};
+
+    #[test]
+    fn growable() {
+        let mut set: BitSet = [3, 64, 200].iter().copied().collect();
+
+        assert!(set.capacity() >= 201);
+        assert!(set.insert(1000));
+        assert!(!set.insert(64));
+        assert!(set.contains(200));
+        assert!(!set.contains(5000));
+        assert_eq!(vec![3, 64, 200, 1000], set.iter().collect::<Vec<_>>());
+
+        assert!(set.remove(200));
+        assert!(!set.remove(200));
+        assert_eq!(3, set.len());
+
+        let other: BitSet = [3, 4, 2000].iter().copied().collect();
+
+        let mut union = set.clone();
+        union.union_with(&other);
+        assert_eq!(vec![3, 4, 64, 1000, 2000], union.iter().collect::<Vec<_>>());
+
+        let mut intersection = set.clone();
+        intersection.intersect_with(&other);
+        assert_eq!(vec![3], intersection.iter().collect::<Vec<_>>());
+
+        let mut difference = set.clone();
+        difference.difference_with(&other);
+        assert_eq!(vec![64, 1000], difference.iter().collect::<Vec<_>>());
+
+        let mut symmetric = set.clone();
+        symmetric.symmetric_difference_with(&other);
+        assert_eq!(
+            vec![4, 64, 1000, 2000],
+            symmetric.iter().collect::<Vec<_>>()
+        );
+
+        assert!(intersection.is_subset(&set));
+        assert!(!set.is_subset(&other));
+        assert!(difference.is_disjoint(&other));
+        assert_eq!(BitSet::with_capacity(10_000), BitSet::new());
+    }
+
+    #[test]
+    fn fixed() {
+        let mut set = FixedBitSet::new(130);
+
+        for i in (0..130).step_by(3) {
+            assert!(set.insert(i));
+        }
+
+        set.set(1, true);
+        set.set(0, false);
+
+        let mut complement = set.clone();
+        complement.complement();
+
+        assert_eq!(130, set.len() + complement.len());
+        assert!(set.is_disjoint(&complement));
+        assert!(!complement.contains(130));
+
+        complement.union_with(&set);
+        assert_eq!(FixedBitSet::full(130), complement);
+        assert!(set.is_subset(&complement));
+        assert!(complement.iter().eq(0..130));
+    }
+
+    #[test]
+    #[should_panic]
+    fn fixed_out_of_bounds() {
+        FixedBitSet::new(10).insert(10);
+    }
+}
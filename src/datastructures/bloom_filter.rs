@@ -0,0 +1,246 @@
+//! Bloom filters: approximate set membership in a fixed amount of memory.
+//!
+//! An item is represented by `k` positions in an array of `m` bits, chosen by `k` hash functions.
+//! Inserting sets the `k` bits, and an item is reported as present if all its bits are set. There are no false
+//! negatives, but an item may be reported as present because other items set all its bits. After `n`
+//! insertions the false positive probability is about `(1 - e^(-kn/m))^k`, which is minimized by
+//! `k = (m / n) ln 2`, giving `m = -n ln p / (ln 2)^2` bits for a target rate `p`.
+//!
+//! The `k` positions are derived from two hashes as `h1 + i * h2` (Kirsch and Mitzenmacher), which performs as
+//! well as `k` independent hash functions.
+
+use std::hash::Hash;
+
+use super::hash_map::hash_with_seed;
+
+/// Optimal number of bits and hash functions for `items` insertions with the given false positive rate.
+fn optimal_parameters(items: usize, false_positive_rate: f64) -> (usize, usize) {
+    assert!(
+        false_positive_rate > 0.0 && false_positive_rate < 1.0,
+        "the false positive rate must be in (0, 1)"
+    );
+
+    let items = items.max(1) as f64;
+    let ln2 = std::f64::consts::LN_2;
+    let bits = (-items * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+    let hashes = (bits / items * ln2).round().max(1.0);
+
+    (bits as usize, hashes as usize)
+}
+
+/// Positions of `item` in a filter of `len` slots.
+fn positions<T: Hash + ?Sized>(item: &T, hashes: usize, len: usize) -> impl Iterator<Item = usize> {
+    let h1 = hash_with_seed(item, 0);
+    // a step of 0 would give the same position `k` times
+    let h2 = hash_with_seed(item, 1) | 1;
+
+    (0..hashes as u64).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % len as u64) as usize)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    len: usize,
+    hashes: usize,
+    /// Number of insertions, used to estimate the current false positive rate.
+    insertions: usize,
+}
+
+impl BloomFilter {
+    /// Creates a filter with `bits` bits and `hashes` hash functions.
+    pub fn new(bits: usize, hashes: usize) -> Self {
+        assert!(bits > 0 && hashes > 0, "empty filter");
+
+        Self {
+            bits: vec![0; bits.div_ceil(64)],
+            len: bits,
+            hashes,
+            insertions: 0,
+        }
+    }
+
+    /// Creates a filter sized to have the given false positive rate after `items` insertions.
+    pub fn with_error_rate(items: usize, false_positive_rate: f64) -> Self {
+        let (bits, hashes) = optimal_parameters(items, false_positive_rate);
+
+        Self::new(bits, hashes)
+    }
+
+    pub fn bits(&self) -> usize {
+        self.len
+    }
+
+    pub fn hashes(&self) -> usize {
+        self.hashes
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        for pos in positions(item, self.hashes, self.len) {
+            self.bits[pos / 64] |= 1 << (pos % 64);
+        }
+
+        self.insertions += 1;
+    }
+
+    /// Returns `false` if `item` was never inserted, `true` if it probably was.
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        positions(item, self.hashes, self.len)
+            .all(|pos| self.bits[pos / 64] & (1 << (pos % 64)) != 0)
+    }
+
+    /// Estimated false positive rate given the number of insertions so far.
+    pub fn false_positive_rate(&self) -> f64 {
+        let k = self.hashes as f64;
+        let fill = 1.0 - (-k * self.insertions as f64 / self.len as f64).exp();
+
+        fill.powf(k)
+    }
+
+    /// Adds every item of `other` to `self`, as if they were inserted in `self`.
+    ///
+    /// Panics if the filters don't have the same size and number of hash functions.
+    pub fn merge(&mut self, other: &BloomFilter) {
+        assert!(
+            self.len == other.len && self.hashes == other.hashes,
+            "cannot merge filters with different parameters"
+        );
+
+        for (word, other) in self.bits.iter_mut().zip(&other.bits) {
+            *word |= other;
+        }
+
+        self.insertions += other.insertions;
+    }
+}
+
+/// Bloom filter with a counter instead of a bit in each slot, so that items can also be removed.
+///
+/// Counters saturate at `u8::MAX`: a saturated counter is never decremented again, which may cause
+/// false positives but never false negatives.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountingBloomFilter {
+    counters: Vec<u8>,
+    hashes: usize,
+}
+
+impl CountingBloomFilter {
+    pub fn new(counters: usize, hashes: usize) -> Self {
+        assert!(counters > 0 && hashes > 0, "empty filter");
+
+        Self {
+            counters: vec![0; counters],
+            hashes,
+        }
+    }
+
+    /// Creates a filter sized to have the given false positive rate with `items` items in it.
+    pub fn with_error_rate(items: usize, false_positive_rate: f64) -> Self {
+        let (counters, hashes) = optimal_parameters(items, false_positive_rate);
+
+        Self::new(counters, hashes)
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        for pos in positions(item, self.hashes, self.counters.len()) {
+            self.counters[pos] = self.counters[pos].saturating_add(1);
+        }
+    }
+
+    /// Removes one occurrence of `item`. Returns `false` and does nothing if it's not in the filter.
+    ///
+    /// Removing an item that was never inserted (but is a false positive) corrupts the filter.
+    pub fn remove<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        if !self.contains(item) {
+            return false;
+        }
+
+        for pos in positions(item, self.hashes, self.counters.len()) {
+            if self.counters[pos] != u8::MAX {
+                self.counters[pos] -= 1;
+            }
+        }
+
+        true
+    }
+
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        self.count(item) > 0
+    }
+
+    /// Upper bound on the number of times `item` was inserted.
+    pub fn count<T: Hash + ?Sized>(&self, item: &T) -> u8 {
+        positions(item, self.hashes, self.counters.len())
+            .map(|pos| self.counters[pos])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Adds the counts of `other` to `self`.
+    ///
+    /// Panics if the filters don't have the same size and number of hash functions.
+    pub fn merge(&mut self, other: &CountingBloomFilter) {
+        assert!(
+            self.counters.len() == other.counters.len() && self.hashes == other.hashes,
+            "cannot merge filters with different parameters"
+        );
+
+        for (counter, other) in self.counters.iter_mut().zip(&other.counters) {
+            *counter = counter.saturating_add(*other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BloomFilter, CountingBloomFilter};
+
+    #[test]
+    fn false_positive_rate() {
+        let mut filter = BloomFilter::with_error_rate(1000, 0.01);
+
+        for i in 0..1000 {
+            filter.insert(&i);
+        }
+
+        assert!((0..1000).all(|i| filter.contains(&i)));
+
+        let false_positives = (1000..11000).filter(|i| filter.contains(i)).count();
+
+        assert!(false_positives < 200, "{} false positives", false_positives);
+        assert!((filter.false_positive_rate() - 0.01).abs() < 0.005);
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = BloomFilter::new(1024, 4);
+        let mut b = BloomFilter::new(1024, 4);
+
+        a.insert("apple");
+        b.insert("banana");
+        a.merge(&b);
+
+        assert!(a.contains("apple"));
+        assert!(a.contains("banana"));
+    }
+
+    #[test]
+    fn counting() {
+        let mut filter = CountingBloomFilter::with_error_rate(100, 0.01);
+        let mut other = CountingBloomFilter::with_error_rate(100, 0.01);
+
+        filter.insert("a");
+        filter.insert("a");
+        other.insert("b");
+        filter.merge(&other);
+
+        assert_eq!(2, filter.count("a"));
+        assert!(filter.contains("b"));
+
+        assert!(filter.remove("a"));
+        assert!(filter.contains("a"));
+        assert!(filter.remove("a"));
+        assert!(!filter.contains("a"));
+        assert!(!filter.remove("a"));
+        assert!(filter.contains("b"));
+    }
+}
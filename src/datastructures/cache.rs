@@ -0,0 +1,290 @@
+//! Caches bounded to a fixed number of entries, and memoization on top of them.
+//!
+//! - `LruCache`: evicts the least recently used entry.
+//! - `LfuCache`: evicts the least frequently used entry, the least recently used one among ties.
+//!
+//! Both keep their entries in doubly linked lists stored in a vector and linked by index, with a hash map from
+//! the keys to the indices, so that lookups, insertions and evictions are all O(1).
+
+pub mod lfu;
+pub mod lru;
+
+pub use lfu::LfuCache;
+pub use lru::LruCache;
+
+/// Index of the end of a list.
+const NIL: usize = usize::MAX;
+
+/// Common interface of the caches in this module.
+pub trait Cache<K, V> {
+    /// Inserts `value` under `key`, evicting an entry if the cache is full. Returns the previous value
+    /// if the key was present.
+    fn put(&mut self, key: K, value: V) -> Option<V>;
+    /// Looks up `key`, which counts as a use of the entry.
+    fn get(&mut self, key: &K) -> Option<&V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn len(&self) -> usize;
+    /// Maximum number of entries.
+    fn capacity(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Called with the entries evicted to make room for new ones.
+type EvictionCallback<K, V> = Box<dyn FnMut(K, V)>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    prev: usize,
+    next: usize,
+}
+
+/// Ends of a list of nodes in a `Slab`.
+#[derive(Debug, Clone, Copy)]
+struct List {
+    head: usize,
+    tail: usize,
+}
+
+impl List {
+    const EMPTY: List = List {
+        head: NIL,
+        tail: NIL,
+    };
+
+    fn is_empty(&self) -> bool {
+        self.head == NIL
+    }
+}
+
+/// Nodes of any number of doubly linked lists, addressed by index. Freed slots are reused.
+struct Slab<K, V> {
+    nodes: Vec<Option<Node<K, V>>>,
+    free: Vec<usize>,
+}
+
+impl<K, V> Slab<K, V> {
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            nodes: Vec::with_capacity(capacity),
+            free: Vec::new(),
+        }
+    }
+
+    /// Stores an unlinked node and returns its index.
+    fn insert(&mut self, key: K, value: V) -> usize {
+        let node = Some(Node {
+            key,
+            value,
+            prev: NIL,
+            next: NIL,
+        });
+
+        match self.free.pop() {
+            Some(idx) => {
+                self.nodes[idx] = node;
+                idx
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    /// Frees the node at `idx`, which must have been unlinked.
+    fn remove(&mut self, idx: usize) -> (K, V) {
+        let node = self.nodes[idx].take().expect("removed a free slot");
+        self.free.push(idx);
+
+        (node.key, node.value)
+    }
+
+    fn node(&self, idx: usize) -> &Node<K, V> {
+        self.nodes[idx].as_ref().expect("accessed a free slot")
+    }
+
+    fn node_mut(&mut self, idx: usize) -> &mut Node<K, V> {
+        self.nodes[idx].as_mut().expect("accessed a free slot")
+    }
+
+    fn push_front(&mut self, list: &mut List, idx: usize) {
+        let head = list.head;
+        let node = self.node_mut(idx);
+        node.prev = NIL;
+        node.next = head;
+
+        if head == NIL {
+            list.tail = idx;
+        } else {
+            self.node_mut(head).prev = idx;
+        }
+
+        list.head = idx;
+    }
+
+    fn unlink(&mut self, list: &mut List, idx: usize) {
+        let (prev, next) = {
+            let node = self.node(idx);
+            (node.prev, node.next)
+        };
+
+        if prev == NIL {
+            list.head = next;
+        } else {
+            self.node_mut(prev).next = next;
+        }
+
+        if next == NIL {
+            list.tail = prev;
+        } else {
+            self.node_mut(next).prev = prev;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.nodes.clear();
+        self.free.clear();
+    }
+}
+
+/// Memoizes a function in a bounded cache, so that memoized algorithms run in bounded memory.
+///
+/// Evicted values are recomputed when needed again, so the cache size trades memory for time.
+pub struct Memoizer<C> {
+    cache: C,
+    hits: usize,
+    misses: usize,
+}
+
+impl<C> Memoizer<C> {
+    pub fn new(cache: C) -> Self {
+        Self {
+            cache,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Returns the cached value for `key`, or computes it with `compute` and caches it.
+    ///
+    /// `compute` is given the memoizer so that it can recursively use it for subproblems.
+    pub fn get_or_insert_with<K, V>(&mut self, key: K, compute: impl FnOnce(&mut Self) -> V) -> V
+    where
+        C: Cache<K, V>,
+        V: Clone,
+    {
+        if let Some(value) = self.cache.get(&key) {
+            self.hits += 1;
+            return value.clone();
+        }
+
+        self.misses += 1;
+
+        let value = compute(self);
+        self.cache.put(key, value.clone());
+
+        value
+    }
+
+    pub fn cache(&self) -> &C {
+        &self.cache
+    }
+
+    pub fn into_cache(self) -> C {
+        self.cache
+    }
+
+    /// Number of lookups answered from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of values computed.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::{Cache, LfuCache, LruCache, Memoizer};
+
+    #[test]
+    fn lru() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&evicted);
+        let mut cache =
+            LruCache::with_eviction_callback(2, move |k, v| log.borrow_mut().push((k, v)));
+
+        assert_eq!(None, cache.put(1, "one"));
+        assert_eq!(None, cache.put(2, "two"));
+        assert_eq!(Some(&"one"), cache.get(&1));
+        assert_eq!(None, cache.put(3, "three"));
+
+        assert_eq!(vec![(2, "two")], *evicted.borrow());
+        assert_eq!(None, cache.get(&2));
+        assert_eq!(Some(&"three"), cache.peek(&3));
+
+        assert_eq!(Some("one"), cache.put(1, "uno"));
+        assert_eq!(None, cache.put(4, "four"));
+        assert_eq!(vec![(2, "two"), (3, "three")], *evicted.borrow());
+
+        assert_eq!(Some("uno"), cache.remove(&1));
+        assert_eq!(1, cache.len());
+        assert_eq!(vec![(&4, &"four")], cache.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn lfu() {
+        let evicted = Rc::new(RefCell::new(Vec::new()));
+        let log = Rc::clone(&evicted);
+        let mut cache = LfuCache::with_eviction_callback(2, move |k, _| log.borrow_mut().push(k));
+
+        cache.put('a', 1);
+        cache.put('b', 2);
+        cache.get(&'a');
+        cache.get(&'a');
+        cache.get(&'b');
+        assert_eq!(Some(3), cache.frequency(&'a'));
+
+        // `b` is used less than `a`
+        cache.put('c', 3);
+        assert_eq!(vec!['b'], *evicted.borrow());
+
+        // `c` is still used less than `a`
+        cache.get(&'c');
+        cache.put('b', 2);
+        assert_eq!(vec!['b', 'c'], *evicted.borrow());
+
+        assert_eq!(Some(1), cache.remove(&'a'));
+        assert_eq!(Some(&2), cache.get(&'b'));
+        assert_eq!(1, cache.len());
+    }
+
+    #[test]
+    fn memoizer() {
+        fn fibonacci(n: u64, memo: &mut Memoizer<impl Cache<u64, u64>>) -> u64 {
+            if n < 2 {
+                n
+            } else {
+                memo.get_or_insert_with(n, |memo| fibonacci(n - 1, memo) + fibonacci(n - 2, memo))
+            }
+        }
+
+        let mut memo = Memoizer::new(LruCache::new(3));
+
+        assert_eq!(12_586_269_025, fibonacci(50, &mut memo));
+        assert_eq!(49, memo.misses());
+        assert!(memo.cache().len() <= 3);
+
+        let mut memo = Memoizer::new(LfuCache::new(3));
+        assert_eq!(6765, fibonacci(20, &mut memo));
+    }
+}
@@ -0,0 +1,180 @@
+//! Least frequently used cache.
+//!
+//! Every entry counts how many times it was used, and the entries with the same count are kept in a list ordered
+//! by recency. An access moves the entry to the front of the next list. The cache also tracks the smallest
+//! count, which only changes by one on an access, or back to 1 on an insertion, so the entry to evict (the back
+//! of the list with the smallest count) is found in O(1).
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{Cache, EvictionCallback, List, Slab};
+
+struct Counted<V> {
+    value: V,
+    frequency: usize,
+}
+
+pub struct LfuCache<K, V> {
+    map: HashMap<K, usize>,
+    slab: Slab<K, Counted<V>>,
+    /// Entries used the same number of times, most recently used first.
+    lists: HashMap<usize, List>,
+    min_frequency: usize,
+    capacity: usize,
+    on_evict: Option<EvictionCallback<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    /// Creates a cache holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "zero capacity");
+
+        Self {
+            map: HashMap::with_capacity(capacity),
+            slab: Slab::with_capacity(capacity),
+            lists: HashMap::new(),
+            min_frequency: 0,
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    /// Creates a cache that calls `on_evict` with every entry evicted to make room for a new one.
+    pub fn with_eviction_callback(capacity: usize, on_evict: impl FnMut(K, V) + 'static) -> Self {
+        let mut cache = Self::new(capacity);
+        cache.on_evict = Some(Box::new(on_evict));
+        cache
+    }
+
+    /// Looks up `key` without counting it as a use.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let idx = *self.map.get(key)?;
+
+        Some(&self.slab.node(idx).value.value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let idx = *self.map.get(key)?;
+        self.touch(idx);
+
+        Some(&mut self.slab.node_mut(idx).value.value)
+    }
+
+    /// Number of times the entry of `key` was used, including its insertion.
+    pub fn frequency(&self, key: &K) -> Option<usize> {
+        let idx = *self.map.get(key)?;
+
+        Some(self.slab.node(idx).value.frequency)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.slab.clear();
+        self.lists.clear();
+        self.min_frequency = 0;
+    }
+
+    /// Removes the node at `idx` from the list of its frequency.
+    fn unlink(&mut self, idx: usize) -> usize {
+        let frequency = self.slab.node(idx).value.frequency;
+        let list = self
+            .lists
+            .get_mut(&frequency)
+            .expect("missing frequency list");
+
+        self.slab.unlink(list, idx);
+
+        if list.is_empty() {
+            self.lists.remove(&frequency);
+        }
+
+        frequency
+    }
+
+    fn link(&mut self, idx: usize, frequency: usize) {
+        self.slab.node_mut(idx).value.frequency = frequency;
+
+        let list = self.lists.entry(frequency).or_insert(List::EMPTY);
+        self.slab.push_front(list, idx);
+    }
+
+    fn touch(&mut self, idx: usize) {
+        let frequency = self.unlink(idx);
+
+        if frequency == self.min_frequency && !self.lists.contains_key(&frequency) {
+            self.min_frequency += 1;
+        }
+
+        self.link(idx, frequency + 1);
+    }
+
+    fn evict(&mut self) {
+        let idx = self.lists[&self.min_frequency].tail;
+        self.unlink(idx);
+
+        let (key, counted) = self.slab.remove(idx);
+        self.map.remove(&key);
+
+        if let Some(on_evict) = &mut self.on_evict {
+            on_evict(key, counted.value);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LfuCache<K, V> {
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&idx) = self.map.get(&key) {
+            self.touch(idx);
+            return Some(std::mem::replace(
+                &mut self.slab.node_mut(idx).value.value,
+                value,
+            ));
+        }
+
+        if self.map.len() == self.capacity {
+            self.evict();
+        }
+
+        let idx = self.slab.insert(
+            key.clone(),
+            Counted {
+                value,
+                frequency: 1,
+            },
+        );
+        self.link(idx, 1);
+        self.map.insert(key, idx);
+        self.min_frequency = 1;
+
+        None
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let idx = *self.map.get(key)?;
+        self.touch(idx);
+
+        Some(&self.slab.node(idx).value.value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let idx = self.map.remove(key)?;
+        // the smallest frequency may become stale, but it's only needed to evict, which can't happen
+        // before an insertion resets it to 1
+        self.unlink(idx);
+
+        Some(self.slab.remove(idx).1.value)
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
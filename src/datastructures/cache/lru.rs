@@ -0,0 +1,159 @@
+//! Least recently used cache.
+//!
+//! The entries are kept in a list ordered by recency: an access moves the entry to the front, and the entry at
+//! the back is evicted when the cache is full.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::{Cache, EvictionCallback, List, Slab, NIL};
+
+pub struct LruCache<K, V> {
+    map: HashMap<K, usize>,
+    slab: Slab<K, V>,
+    /// Most recently used first.
+    order: List,
+    capacity: usize,
+    on_evict: Option<EvictionCallback<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    /// Creates a cache holding at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "zero capacity");
+
+        Self {
+            map: HashMap::with_capacity(capacity),
+            slab: Slab::with_capacity(capacity),
+            order: List::EMPTY,
+            capacity,
+            on_evict: None,
+        }
+    }
+
+    /// Creates a cache that calls `on_evict` with every entry evicted to make room for a new one.
+    pub fn with_eviction_callback(capacity: usize, on_evict: impl FnMut(K, V) + 'static) -> Self {
+        let mut cache = Self::new(capacity);
+        cache.on_evict = Some(Box::new(on_evict));
+        cache
+    }
+
+    /// Looks up `key` without counting it as a use.
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        let idx = *self.map.get(key)?;
+
+        Some(&self.slab.node(idx).value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let idx = *self.map.get(key)?;
+        self.touch(idx);
+
+        Some(&mut self.slab.node_mut(idx).value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.slab.clear();
+        self.order = List::EMPTY;
+    }
+
+    /// Iterates over the entries from the most to the least recently used.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slab: &self.slab,
+            next: self.order.head,
+            len: self.map.len(),
+        }
+    }
+
+    fn touch(&mut self, idx: usize) {
+        self.slab.unlink(&mut self.order, idx);
+        self.slab.push_front(&mut self.order, idx);
+    }
+
+    fn evict(&mut self) {
+        let idx = self.order.tail;
+        self.slab.unlink(&mut self.order, idx);
+
+        let (key, value) = self.slab.remove(idx);
+        self.map.remove(&key);
+
+        if let Some(on_evict) = &mut self.on_evict {
+            on_evict(key, value);
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LruCache<K, V> {
+    fn put(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(&idx) = self.map.get(&key) {
+            self.touch(idx);
+            return Some(std::mem::replace(&mut self.slab.node_mut(idx).value, value));
+        }
+
+        if self.map.len() == self.capacity {
+            self.evict();
+        }
+
+        let idx = self.slab.insert(key.clone(), value);
+        self.slab.push_front(&mut self.order, idx);
+        self.map.insert(key, idx);
+
+        None
+    }
+
+    fn get(&mut self, key: &K) -> Option<&V> {
+        let idx = *self.map.get(key)?;
+        self.touch(idx);
+
+        Some(&self.slab.node(idx).value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let idx = self.map.remove(key)?;
+        self.slab.unlink(&mut self.order, idx);
+
+        Some(self.slab.remove(idx).1)
+    }
+
+    fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+pub struct Iter<'a, K, V> {
+    slab: &'a Slab<K, V>,
+    next: usize,
+    len: usize,
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next == NIL {
+            return None;
+        }
+
+        let node = self.slab.node(self.next);
+        self.next = node.next;
+        self.len -= 1;
+
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
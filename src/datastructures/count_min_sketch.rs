@@ -0,0 +1,137 @@
+//! Count-min sketch: approximate frequencies of the items of a stream.
+//!
+//! The sketch is a `depth x width` matrix of counters, each row with its own hash function. Adding an item
+//! increments one counter per row, and the estimate of its frequency is the minimum of those counters.
+//! Collisions only ever increase counters, so the estimate never undercounts. With `width = e / epsilon`
+//! and `depth = ln(1 / delta)`, it overcounts by more than `epsilon * total` with probability at most `delta`.
+
+use std::hash::Hash;
+
+use super::hash_map::hash_with_seed;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CountMinSketch {
+    width: usize,
+    depth: usize,
+    /// Row-major `depth x width` matrix.
+    counters: Vec<u64>,
+    total: u64,
+}
+
+impl CountMinSketch {
+    pub fn new(width: usize, depth: usize) -> Self {
+        assert!(width > 0 && depth > 0, "empty sketch");
+
+        Self {
+            width,
+            depth,
+            counters: vec![0; width * depth],
+            total: 0,
+        }
+    }
+
+    /// Creates a sketch whose estimates exceed the true count by more than `epsilon * total`
+    /// with probability at most `delta`.
+    pub fn with_error(epsilon: f64, delta: f64) -> Self {
+        assert!(
+            epsilon > 0.0 && delta > 0.0 && delta < 1.0,
+            "invalid error bounds"
+        );
+
+        let width = (std::f64::consts::E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+
+        Self::new(width, depth)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Sum of all the counts added.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Adds `count` occurrences of `item`.
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T, count: u64) {
+        for row in 0..self.depth {
+            let cell = self.cell(item, row);
+            self.counters[cell] += count;
+        }
+
+        self.total += count;
+    }
+
+    /// Upper bound on the number of occurrences of `item`.
+    pub fn estimate<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        (0..self.depth)
+            .map(|row| self.counters[self.cell(item, row)])
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Adds the counts of `other` to `self`, as if its items were added to `self`.
+    ///
+    /// Panics if the sketches don't have the same dimensions.
+    pub fn merge(&mut self, other: &CountMinSketch) {
+        assert!(
+            self.width == other.width && self.depth == other.depth,
+            "cannot merge sketches with different dimensions"
+        );
+
+        for (counter, other) in self.counters.iter_mut().zip(&other.counters) {
+            *counter += other;
+        }
+
+        self.total += other.total;
+    }
+
+    fn cell<T: Hash + ?Sized>(&self, item: &T, row: usize) -> usize {
+        row * self.width + (hash_with_seed(item, row as u64) % self.width as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CountMinSketch;
+
+    #[test]
+    fn estimates() {
+        let mut sketch = CountMinSketch::with_error(0.01, 0.01);
+        let mut other = CountMinSketch::with_error(0.01, 0.01);
+
+        // item `i` appears `i` times
+        for i in 1..=200u64 {
+            if i <= 100 {
+                sketch.add(&i, i);
+            } else {
+                other.add(&i, i);
+            }
+        }
+
+        sketch.merge(&other);
+
+        let total = sketch.total();
+        assert_eq!(200 * 201 / 2, total);
+
+        let mut within_bound = 0;
+
+        for i in 1..=200u64 {
+            let estimate = sketch.estimate(&i);
+
+            assert!(estimate >= i);
+
+            if estimate as f64 <= i as f64 + 0.01 * total as f64 {
+                within_bound += 1;
+            }
+        }
+
+        assert!(within_bound >= 195);
+        assert_eq!(0, CountMinSketch::new(10, 3).estimate("never added"));
+    }
+}
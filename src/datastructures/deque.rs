@@ -0,0 +1,472 @@
+//! Double-ended queue backed by a growable ring buffer.
+//!
+//! The elements live in a circular buffer: `head` is the physical index of the front element and the
+//! `len` elements following it (wrapping around the end of the buffer) are the content of the deque.
+//!
+//! # Amortized analysis
+//! When the buffer is full its capacity is doubled and every element is moved to the new buffer.
+//! A single push can therefore cost O(n). Take the potential `Φ = max(0, 2 * len - capacity)`, which is 0
+//! for a new deque and never negative. A push without growing costs 1 and raises `Φ` by at most 2, and a pop
+//! costs 1 and doesn't raise `Φ`. When the buffer grows `len == capacity`, so `Φ = len`: moving the `len`
+//! elements and pushing costs `len + 1`, and afterwards `Φ = 2`, so this push costs 3 amortized as well.
+//! Hence any sequence of `m` pushes and pops costs O(m) in total, that is O(1) amortized per operation.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Index, IndexMut};
+use std::slice;
+
+const MIN_CAPACITY: usize = 4;
+
+pub struct Deque<T> {
+    /// Slots `head..head + len` (modulo the capacity) are `Some`, every other slot is `None`.
+    buf: Vec<Option<T>>,
+    head: usize,
+    len: usize,
+}
+
+impl<T> Default for Deque<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deque<T> {
+    /// Creates an empty deque. It won't allocate until the first element is pushed.
+    pub fn new() -> Self {
+        Self {
+            buf: Vec::new(),
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let mut buf = Vec::with_capacity(capacity);
+        buf.resize_with(capacity, || None);
+
+        Self {
+            buf,
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buf.len()
+    }
+
+    /// Adds `value` at the back. O(1) amortized, see the module documentation.
+    pub fn push_back(&mut self, value: T) {
+        self.grow_if_full();
+
+        let idx = self.physical(self.len);
+        self.buf[idx] = Some(value);
+        self.len += 1;
+    }
+
+    /// Adds `value` at the front. O(1) amortized, see the module documentation.
+    pub fn push_front(&mut self, value: T) {
+        self.grow_if_full();
+
+        self.head = self.physical(self.capacity() - 1);
+        self.buf[self.head] = Some(value);
+        self.len += 1;
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let value = self.buf[self.head].take();
+        self.head = self.physical(1);
+        self.len -= 1;
+
+        value
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+
+        self.len -= 1;
+        let idx = self.physical(self.len);
+
+        self.buf[idx].take()
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        self.len.checked_sub(1).and_then(|idx| self.get(idx))
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        self.get_mut(0)
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        self.len
+            .checked_sub(1)
+            .and_then(move |idx| self.get_mut(idx))
+    }
+
+    /// Returns the element at position `idx`, counting from the front.
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        if idx < self.len {
+            self.buf[self.physical(idx)].as_ref()
+        } else {
+            None
+        }
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        if idx < self.len {
+            let idx = self.physical(idx);
+            self.buf[idx].as_mut()
+        } else {
+            None
+        }
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_back().is_some() {}
+        self.head = 0;
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        let (front, back) = self.as_slices();
+
+        Iter(front.iter().chain(back.iter()))
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        let (front, back) = self.as_mut_slices();
+
+        IterMut(front.iter_mut().chain(back.iter_mut()))
+    }
+
+    /// Maps a logical index (0 is the front) to the index in the buffer.
+    fn physical(&self, idx: usize) -> usize {
+        let idx = self.head + idx;
+
+        if idx >= self.capacity() {
+            idx - self.capacity()
+        } else {
+            idx
+        }
+    }
+
+    /// The occupied slots, in order, as at most two contiguous slices.
+    fn as_slices(&self) -> (&[Option<T>], &[Option<T>]) {
+        let end = self.head + self.len;
+
+        if end <= self.capacity() {
+            (&self.buf[self.head..end], &[])
+        } else {
+            let (wrapped, front) = self.buf.split_at(self.head);
+            (front, &wrapped[..end - self.capacity()])
+        }
+    }
+
+    fn as_mut_slices(&mut self) -> (&mut [Option<T>], &mut [Option<T>]) {
+        let end = self.head + self.len;
+        let capacity = self.capacity();
+
+        if end <= capacity {
+            (&mut self.buf[self.head..end], &mut [])
+        } else {
+            let (wrapped, front) = self.buf.split_at_mut(self.head);
+            (front, &mut wrapped[..end - capacity])
+        }
+    }
+
+    /// Doubles the capacity when the buffer is full, moving the elements so that the front is at index 0.
+    fn grow_if_full(&mut self) {
+        if self.len < self.capacity() {
+            return;
+        }
+
+        let new_capacity = (2 * self.capacity()).max(MIN_CAPACITY);
+        let mut buf = Vec::with_capacity(new_capacity);
+
+        for idx in 0..self.len {
+            let idx = self.physical(idx);
+            buf.push(self.buf[idx].take());
+        }
+
+        buf.resize_with(new_capacity, || None);
+
+        self.buf = buf;
+        self.head = 0;
+    }
+}
+
+impl<T> Index<usize> for Deque<T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &T {
+        self.get(idx).expect("index out of bounds")
+    }
+}
+
+impl<T> IndexMut<usize> for Deque<T> {
+    fn index_mut(&mut self, idx: usize) -> &mut T {
+        self.get_mut(idx).expect("index out of bounds")
+    }
+}
+
+impl<T: Clone> Clone for Deque<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Deque<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Deque<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for Deque<T> {}
+
+impl<T> FromIterator<T> for Deque<T> {
+    fn from_iter<It: IntoIterator<Item = T>>(iter: It) -> Self {
+        let mut deque = Self::new();
+        deque.extend(iter);
+        deque
+    }
+}
+
+impl<T> Extend<T> for Deque<T> {
+    fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
+        iter.into_iter().for_each(|value| self.push_back(value));
+    }
+}
+
+pub struct Iter<'a, T>(std::iter::Chain<slice::Iter<'a, Option<T>>, slice::Iter<'a, Option<T>>>);
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.0
+            .next()
+            .map(|slot| slot.as_ref().expect("occupied slot"))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        self.0
+            .next_back()
+            .map(|slot| slot.as_ref().expect("occupied slot"))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+pub struct IterMut<'a, T>(
+    std::iter::Chain<slice::IterMut<'a, Option<T>>, slice::IterMut<'a, Option<T>>>,
+);
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.0
+            .next()
+            .map(|slot| slot.as_mut().expect("occupied slot"))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        self.0
+            .next_back()
+            .map(|slot| slot.as_mut().expect("occupied slot"))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+pub struct IntoIter<T>(Deque<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for Deque<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Deque<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Deque<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::Deque;
+    use crate::random::Rng;
+
+    #[test]
+    fn push_pop_both_ends() {
+        let mut deque = Deque::new();
+
+        assert_eq!(None, deque.pop_front());
+        assert_eq!(None, deque.back());
+
+        for i in 0..10 {
+            deque.push_back(i);
+            deque.push_front(-i);
+        }
+
+        assert_eq!(20, deque.len());
+        assert_eq!(Some(&-9), deque.front());
+        assert_eq!(Some(&9), deque.back());
+        assert_eq!(0, deque[9]);
+        assert_eq!(0, deque[10]);
+
+        for i in (0..10).rev() {
+            assert_eq!(Some(-i), deque.pop_front());
+            assert_eq!(Some(i), deque.pop_back());
+        }
+
+        assert!(deque.is_empty());
+    }
+
+    #[test]
+    fn wrap_around() {
+        let mut deque = Deque::with_capacity(4);
+
+        // move the head towards the end of the buffer so that the content wraps
+        deque.extend(0..3);
+        deque.pop_front();
+        deque.pop_front();
+        deque.extend(3..6);
+
+        assert_eq!(4, deque.capacity());
+        assert_eq!(vec![2, 3, 4, 5], deque.iter().copied().collect::<Vec<_>>());
+        assert_eq!(
+            vec![5, 4, 3, 2],
+            deque.iter().rev().copied().collect::<Vec<_>>()
+        );
+
+        deque.iter_mut().for_each(|v| *v *= 2);
+        deque.push_back(12);
+
+        assert_eq!(8, deque.capacity());
+        assert_eq!(vec![4, 6, 8, 10, 12], deque.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn against_vec_deque() {
+        let mut rng = Rng::new(26);
+        let mut deque = Deque::new();
+        let mut expected = VecDeque::new();
+
+        for i in 0..3000 {
+            // pushes slightly outnumber pops, so the buffer grows while its content wraps
+            match rng.below(9) {
+                0 | 1 => {
+                    deque.push_back(i);
+                    expected.push_back(i);
+                }
+                2 | 3 => {
+                    deque.push_front(i);
+                    expected.push_front(i);
+                }
+                4 => assert_eq!(expected.pop_front(), deque.pop_front()),
+                5 => assert_eq!(expected.pop_back(), deque.pop_back()),
+                6 => {
+                    let idx = rng.below(expected.len() as u64 + 1) as usize;
+
+                    if let Some(value) = deque.get_mut(idx) {
+                        *value = -i;
+                    }
+                    if let Some(value) = expected.get_mut(idx) {
+                        *value = -i;
+                    }
+                }
+                _ => {
+                    let idx = rng.below(expected.len() as u64 + 1) as usize;
+                    assert_eq!(expected.get(idx), deque.get(idx));
+                }
+            }
+
+            assert_eq!(expected.len(), deque.len());
+            assert_eq!(expected.front(), deque.front());
+            assert_eq!(expected.back(), deque.back());
+        }
+
+        assert!(expected.iter().eq(deque.iter()));
+        assert!(expected.iter().rev().eq(deque.iter().rev()));
+
+        deque.iter_mut().for_each(|value| *value += 1);
+        expected.iter_mut().for_each(|value| *value += 1);
+        assert!(expected.into_iter().eq(deque));
+    }
+}
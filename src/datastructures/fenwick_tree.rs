@@ -0,0 +1,389 @@
+//! Fenwick trees (binary indexed trees) for prefix sums with updates.
+//!
+//! The tree is an array where position `i` (1-based) stores the sum of the `i & -i` elements ending at `i`,
+//! i.e. a block whose length is the lowest set bit of `i`. A prefix `0..end` is the disjoint union of the
+//! blocks found by repeatedly clearing the lowest set bit of `end`, and an element is contained in the blocks
+//! found by repeatedly adding the lowest set bit to its position. Both walks take O(log n) steps.
+//!
+//! Compared to a `SegmentTree<Sum<T>>` it uses half the memory and has smaller constants, but it only works
+//! for invertible operations like the sum, since range sums are computed as differences of prefix sums.
+
+use std::convert::TryFrom;
+use std::ops::{Add, Mul, RangeBounds, Sub};
+
+use super::segment_tree::to_bounds;
+
+/// Lowest set bit of `i`.
+fn lsb(i: usize) -> usize {
+    i & i.wrapping_neg()
+}
+
+pub struct FenwickTree<T> {
+    /// 1-based: `tree[0]` is unused.
+    tree: Vec<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> FenwickTree<T> {
+    /// Builds the tree in O(n).
+    pub fn new(values: Vec<T>) -> Self {
+        let mut tree = Vec::with_capacity(values.len() + 1);
+        tree.push(T::default());
+        tree.extend(values);
+
+        // Every block adds its total to the smallest block containing it.
+        for i in 1..tree.len() {
+            let parent = i + lsb(i);
+
+            if parent < tree.len() {
+                tree[parent] = tree[parent] + tree[i];
+            }
+        }
+
+        Self { tree }
+    }
+
+    /// Creates a tree of `len` zeroes.
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            tree: vec![T::default(); len + 1],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `delta` to the element at `idx` in O(log n).
+    pub fn add(&mut self, idx: usize, delta: T) {
+        assert!(idx < self.len(), "index {} out of bounds", idx);
+
+        let mut i = idx + 1;
+
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i] + delta;
+            i += lsb(i);
+        }
+    }
+
+    /// Subtracts `delta` from the element at `idx` in O(log n).
+    pub fn sub(&mut self, idx: usize, delta: T) {
+        assert!(idx < self.len(), "index {} out of bounds", idx);
+
+        let mut i = idx + 1;
+
+        while i < self.tree.len() {
+            self.tree[i] = self.tree[i] - delta;
+            i += lsb(i);
+        }
+    }
+
+    /// Sum of the elements in `0..end`, in O(log n).
+    pub fn prefix_sum(&self, end: usize) -> T {
+        assert!(end <= self.len(), "end {} out of bounds", end);
+
+        let mut sum = T::default();
+        let mut i = end;
+
+        while i > 0 {
+            sum = sum + self.tree[i];
+            i -= lsb(i);
+        }
+
+        sum
+    }
+
+    /// Sum of the elements in `range`, in O(log n).
+    pub fn range_sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = to_bounds(range, self.len());
+
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+
+    pub fn get(&self, idx: usize) -> T {
+        self.range_sum(idx..=idx)
+    }
+
+    pub fn set(&mut self, idx: usize, value: T) {
+        let current = self.get(idx);
+
+        self.add(idx, value - current);
+    }
+
+    /// Returns the smallest `idx` such that `prefix_sum(idx + 1) >= target`, or `None` if the total is smaller.
+    ///
+    /// Works in O(log n) by descending the implicit tree, and requires every element to be non-negative,
+    /// so that prefix sums are sorted.
+    pub fn lower_bound(&self, target: T) -> Option<usize>
+    where
+        T: Ord,
+    {
+        if target <= T::default() {
+            return if self.is_empty() { None } else { Some(0) };
+        }
+
+        let mut pos = 0;
+        let mut remaining = target;
+        let mut step = self.len().next_power_of_two();
+
+        // Invariant: prefix_sum(pos) < target and remaining == target - prefix_sum(pos).
+        while step > 0 {
+            let next = pos + step;
+
+            if next < self.tree.len() && self.tree[next] < remaining {
+                pos = next;
+                remaining = remaining - self.tree[next];
+            }
+
+            step /= 2;
+        }
+
+        if pos < self.len() {
+            Some(pos)
+        } else {
+            None
+        }
+    }
+}
+
+/// Fenwick tree supporting both range updates and range sums in O(log n).
+///
+/// Adding `x` to `l..r` changes the prefix sum of `0..end` by `x * (min(end, r) - l)` for `end > l`.
+/// This is tracked with two trees: `slope`, where `x` is added at `l` and subtracted at `r`, and `offset`,
+/// where `x * l` is added at `l` and `x * r` subtracted at `r`. Then
+/// `prefix_sum(end) = slope.prefix_sum(end) * end - offset.prefix_sum(end)`.
+///
+/// The intermediate values can be negative even if the elements aren't, so `T` should be a signed type.
+pub struct RangeFenwickTree<T> {
+    slope: FenwickTree<T>,
+    offset: FenwickTree<T>,
+}
+
+impl<T> RangeFenwickTree<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + TryFrom<usize>,
+{
+    pub fn new(values: Vec<T>) -> Self {
+        let len = values.len();
+        let mut tree = Self::with_len(len);
+
+        for (idx, value) in values.into_iter().enumerate() {
+            tree.add_range(idx..=idx, value);
+        }
+
+        tree
+    }
+
+    pub fn with_len(len: usize) -> Self {
+        Self {
+            slope: FenwickTree::with_len(len + 1),
+            offset: FenwickTree::with_len(len + 1),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.slope.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `delta` to every element in `range`, in O(log n).
+    pub fn add_range(&mut self, range: impl RangeBounds<usize>, delta: T) {
+        let (start, end) = to_bounds(range, self.len());
+
+        // `slope` and `offset` have one extra slot, so that `end == len` can be updated too.
+        self.slope.add(start, delta);
+        self.slope.sub(end, delta);
+        self.offset.add(start, delta * Self::convert(start));
+        self.offset.sub(end, delta * Self::convert(end));
+    }
+
+    /// Sum of the elements in `0..end`, in O(log n).
+    pub fn prefix_sum(&self, end: usize) -> T {
+        assert!(end <= self.len(), "end {} out of bounds", end);
+
+        self.slope.prefix_sum(end) * Self::convert(end) - self.offset.prefix_sum(end)
+    }
+
+    /// Sum of the elements in `range`, in O(log n).
+    pub fn range_sum(&self, range: impl RangeBounds<usize>) -> T {
+        let (start, end) = to_bounds(range, self.len());
+
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+
+    fn convert(idx: usize) -> T {
+        T::try_from(idx).ok().expect("index must fit in T")
+    }
+}
+
+/// Two-dimensional Fenwick tree: point updates and rectangle sums in O(log n * log m).
+///
+/// Each row of blocks is itself a Fenwick tree over the columns.
+pub struct FenwickTree2D<T> {
+    rows: usize,
+    cols: usize,
+    /// 1-based in both dimensions, row-major with `cols + 1` columns.
+    tree: Vec<T>,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Sub<Output = T>> FenwickTree2D<T> {
+    /// Creates a `rows * cols` grid of zeroes.
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            tree: vec![T::default(); (rows + 1) * (cols + 1)],
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Adds `delta` to the cell `(row, col)`.
+    pub fn add(&mut self, row: usize, col: usize, delta: T) {
+        assert!(
+            row < self.rows && col < self.cols,
+            "cell ({}, {}) out of bounds",
+            row,
+            col
+        );
+
+        let mut i = row + 1;
+
+        while i <= self.rows {
+            let mut j = col + 1;
+
+            while j <= self.cols {
+                let cell = i * (self.cols + 1) + j;
+                self.tree[cell] = self.tree[cell] + delta;
+                j += lsb(j);
+            }
+
+            i += lsb(i);
+        }
+    }
+
+    /// Sum of the cells in `0..row_end` x `0..col_end`.
+    pub fn prefix_sum(&self, row_end: usize, col_end: usize) -> T {
+        assert!(row_end <= self.rows && col_end <= self.cols);
+
+        let mut sum = T::default();
+        let mut i = row_end;
+
+        while i > 0 {
+            let mut j = col_end;
+
+            while j > 0 {
+                sum = sum + self.tree[i * (self.cols + 1) + j];
+                j -= lsb(j);
+            }
+
+            i -= lsb(i);
+        }
+
+        sum
+    }
+
+    /// Sum of the cells in the rectangle `rows` x `cols`, by inclusion-exclusion of four prefix sums.
+    pub fn rect_sum(&self, rows: impl RangeBounds<usize>, cols: impl RangeBounds<usize>) -> T {
+        let (r1, r2) = to_bounds(rows, self.rows);
+        let (c1, c2) = to_bounds(cols, self.cols);
+
+        self.prefix_sum(r2, c2) - self.prefix_sum(r1, c2) - self.prefix_sum(r2, c1)
+            + self.prefix_sum(r1, c1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FenwickTree, FenwickTree2D, RangeFenwickTree};
+
+    #[test]
+    fn prefix_and_range_sums() {
+        let mut values = vec![3i64, -1, 4, 1, -5, 9, 2, -6];
+        let mut tree = FenwickTree::new(values.clone());
+
+        tree.add(2, 10);
+        values[2] += 10;
+        tree.set(5, 0);
+        values[5] = 0;
+
+        for l in 0..=values.len() {
+            assert_eq!(values[..l].iter().sum::<i64>(), tree.prefix_sum(l));
+
+            for r in l..=values.len() {
+                assert_eq!(values[l..r].iter().sum::<i64>(), tree.range_sum(l..r));
+            }
+        }
+
+        assert_eq!(-6, tree.get(7));
+    }
+
+    #[test]
+    fn lower_bound() {
+        let tree = FenwickTree::new(vec![2u32, 0, 3, 1, 0, 4]);
+
+        assert_eq!(Some(0), tree.lower_bound(0));
+        assert_eq!(Some(0), tree.lower_bound(2));
+        assert_eq!(Some(2), tree.lower_bound(3));
+        assert_eq!(Some(2), tree.lower_bound(5));
+        assert_eq!(Some(3), tree.lower_bound(6));
+        assert_eq!(Some(5), tree.lower_bound(10));
+        assert_eq!(None, tree.lower_bound(11));
+        assert_eq!(None, FenwickTree::<u32>::with_len(0).lower_bound(1));
+    }
+
+    #[test]
+    fn range_update_range_query() {
+        let mut values = vec![1i64, 2, 3, 4, 5, 6];
+        let mut tree = RangeFenwickTree::new(values.clone());
+
+        for &(l, r, delta) in &[(0, 3, 5), (2, 6, -2), (4, 5, 7), (0, 6, 1)] {
+            tree.add_range(l..r, delta);
+            values[l..r].iter_mut().for_each(|v| *v += delta);
+
+            for l in 0..=values.len() {
+                for r in l..=values.len() {
+                    assert_eq!(values[l..r].iter().sum::<i64>(), tree.range_sum(l..r));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn two_dimensional() {
+        let mut grid = [[0i32; 4]; 3];
+        let mut tree = FenwickTree2D::new(3, 4);
+
+        for &(r, c, delta) in &[(0, 0, 1), (1, 2, 5), (2, 3, -2), (1, 1, 3), (0, 3, 4)] {
+            tree.add(r, c, delta);
+            grid[r][c] += delta;
+        }
+
+        for r1 in 0..=3 {
+            for r2 in r1..=3 {
+                for c1 in 0..=4 {
+                    for c2 in c1..=4 {
+                        let expected: i32 = grid[r1..r2]
+                            .iter()
+                            .map(|row| row[c1..c2].iter().sum::<i32>())
+                            .sum();
+
+                        assert_eq!(expected, tree.rect_sum(r1..r2, c1..c2));
+                    }
+                }
+            }
+        }
+    }
+}
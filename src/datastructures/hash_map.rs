@@ -0,0 +1,234 @@
+//! Hash maps built from scratch with different collision strategies.
+//!
+//! All the maps implement the `Map` trait, so they can be used interchangeably and compared through `Stats`:
+//!
+//! - `ChainingMap`: every bucket is a list of the entries hashing to it.
+//! - `LinearProbingMap`: open addressing, a colliding key tries the following slots; removal leaves tombstones.
+//! - `RobinHoodMap`: linear probing where an insertion steals the slot of entries closer to their home slot,
+//!   which keeps probe lengths short and even. Removal shifts the following entries back instead of using tombstones.
+//! - `CuckooMap`: two tables with independent hash functions, each key is in one of its two possible slots,
+//!   so lookups take at most two probes. An insertion may kick out other keys into their alternative slot.
+//!
+//! The hash functions are deterministic: FNV-1a followed by a bit mixer, see `hash_with_seed`.
+
+pub mod chaining;
+pub mod cuckoo;
+pub mod linear_probing;
+pub mod robin_hood;
+
+pub use chaining::ChainingMap;
+pub use cuckoo::CuckooMap;
+pub use linear_probing::LinearProbingMap;
+pub use robin_hood::RobinHoodMap;
+
+use std::hash::{Hash, Hasher};
+
+/// Capacity of a map when the first entry is inserted.
+const INITIAL_CAPACITY: usize = 8;
+
+/// Common interface of the hash maps in this module.
+pub trait Map<K: Hash + Eq, V> {
+    /// Inserts `value` under `key`, returning the previous value if the key was present.
+    fn insert(&mut self, key: K, value: V) -> Option<V>;
+    fn get(&self, key: &K) -> Option<&V>;
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+    fn remove(&mut self, key: &K) -> Option<V>;
+    fn len(&self) -> usize;
+    /// Number of slots (or buckets) currently allocated.
+    fn capacity(&self) -> usize;
+    fn stats(&self) -> Stats;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    fn load_factor(&self) -> f64 {
+        if self.capacity() == 0 {
+            0.0
+        } else {
+            self.len() as f64 / self.capacity() as f64
+        }
+    }
+}
+
+/// Statistics to compare the collision strategies empirically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub len: usize,
+    pub capacity: usize,
+    pub load_factor: f64,
+    /// Number of times the table has been rebuilt.
+    pub resizes: usize,
+    /// Number of slots inspected by the longest successful lookup.
+    pub max_probe_length: usize,
+    /// Average number of slots inspected by a successful lookup.
+    pub average_probe_length: f64,
+}
+
+impl Stats {
+    /// Computes the statistics given the probe length of every entry.
+    fn new(capacity: usize, resizes: usize, probe_lengths: impl Iterator<Item = usize>) -> Self {
+        let (mut len, mut total, mut max) = (0, 0, 0);
+
+        for probe_length in probe_lengths {
+            len += 1;
+            total += probe_length;
+            max = max.max(probe_length);
+        }
+
+        Self {
+            len,
+            capacity,
+            load_factor: if capacity == 0 {
+                0.0
+            } else {
+                len as f64 / capacity as f64
+            },
+            resizes,
+            max_probe_length: max,
+            average_probe_length: if len == 0 {
+                0.0
+            } else {
+                total as f64 / len as f64
+            },
+        }
+    }
+}
+
+/// 64-bit FNV-1a hasher, with the output passed through a mixer (the SplitMix64 finalizer)
+/// so that the low bits used to pick a slot depend on every input byte.
+#[derive(Debug, Clone)]
+pub struct FnvHasher {
+    state: u64,
+}
+
+impl FnvHasher {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub fn new() -> Self {
+        Self {
+            state: Self::OFFSET_BASIS,
+        }
+    }
+
+    /// A hasher whose output is independent of the hashers with a different seed.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut hasher = Self::new();
+        hasher.write_u64(seed);
+        hasher
+    }
+}
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.state ^= byte as u64;
+            self.state = self.state.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// Deterministic hash of `key`. Different seeds give different hash functions.
+pub fn hash_with_seed<K: Hash + ?Sized>(key: &K, seed: u64) -> u64 {
+    let mut hasher = FnvHasher::with_seed(seed);
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{hash_with_seed, ChainingMap, CuckooMap, LinearProbingMap, Map, RobinHoodMap};
+    use crate::random::Rng;
+
+    /// Runs the same workload on a map and checks it against the standard library.
+    fn check_map(mut map: impl Map<u64, u64>) {
+        let mut rng = Rng::new(31);
+        let mut reference = std::collections::HashMap::new();
+
+        for i in 0..2000u64 {
+            let key = rng.below(1000);
+
+            assert_eq!(reference.insert(key, i), map.insert(key, i));
+
+            if i % 3 == 0 {
+                let key = rng.below(1000);
+                assert_eq!(reference.remove(&key), map.remove(&key));
+            }
+        }
+
+        assert_eq!(reference.len(), map.len());
+
+        for key in 0..1100 {
+            assert_eq!(reference.get(&key), map.get(&key));
+        }
+
+        if let Some(value) = map.get_mut(&7) {
+            *value = 42;
+            assert_eq!(Some(&42), map.get(&7));
+        }
+
+        let stats = map.stats();
+
+        assert_eq!(map.len(), stats.len);
+        assert_eq!(map.capacity(), stats.capacity);
+        assert!(stats.load_factor <= 1.0);
+        assert!(stats.resizes > 0);
+        assert!(stats.average_probe_length >= 1.0);
+        assert!(stats.max_probe_length as f64 >= stats.average_probe_length);
+    }
+
+    #[test]
+    fn chaining() {
+        check_map(ChainingMap::new());
+    }
+
+    #[test]
+    fn linear_probing() {
+        check_map(LinearProbingMap::new());
+    }
+
+    #[test]
+    fn robin_hood() {
+        check_map(RobinHoodMap::new());
+    }
+
+    #[test]
+    fn cuckoo() {
+        check_map(CuckooMap::new());
+
+        let stats = {
+            let mut map = CuckooMap::new();
+            (0..500u64).for_each(|i| {
+                map.insert(i, i);
+            });
+            map.stats()
+        };
+
+        assert!(stats.max_probe_length <= 2);
+    }
+
+    #[test]
+    fn deterministic_hash() {
+        assert_eq!(hash_with_seed("key", 0), hash_with_seed("key", 0));
+        assert_ne!(hash_with_seed("key", 0), hash_with_seed("key", 1));
+        assert_ne!(hash_with_seed("key", 0), hash_with_seed("kez", 0));
+    }
+}
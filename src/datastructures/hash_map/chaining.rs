@@ -0,0 +1,126 @@
+//! Separate chaining: each bucket holds the list of entries hashing to it.
+//!
+//! With `n` entries in `m` buckets, a lookup inspects `1 + n / (2m)` entries on average if it succeeds.
+//! The number of buckets is doubled when the load factor exceeds `MAX_LOAD_FACTOR`, so that chains stay short.
+
+use std::hash::Hash;
+
+use super::{hash_with_seed, Map, Stats, INITIAL_CAPACITY};
+
+const MAX_LOAD_FACTOR: f64 = 1.0;
+
+pub struct ChainingMap<K, V> {
+    buckets: Vec<Vec<(K, V)>>,
+    len: usize,
+    resizes: usize,
+}
+
+impl<K: Hash + Eq, V> Default for ChainingMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> ChainingMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            buckets: Vec::new(),
+            len: 0,
+            resizes: 0,
+        }
+    }
+
+    fn bucket(&self, key: &K) -> usize {
+        hash_with_seed(key, 0) as usize & (self.buckets.len() - 1)
+    }
+
+    fn resize(&mut self, buckets: usize) {
+        let old = std::mem::replace(
+            &mut self.buckets,
+            (0..buckets).map(|_| Vec::new()).collect(),
+        );
+
+        for (key, value) in old.into_iter().flatten() {
+            let bucket = self.bucket(&key);
+            self.buckets[bucket].push((key, value));
+        }
+
+        self.resizes += 1;
+    }
+}
+
+impl<K: Hash + Eq, V> Map<K, V> for ChainingMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.buckets.is_empty() {
+            self.resize(INITIAL_CAPACITY);
+        }
+
+        let bucket = self.bucket(&key);
+
+        if let Some((_, old)) = self.buckets[bucket].iter_mut().find(|(k, _)| *k == key) {
+            return Some(std::mem::replace(old, value));
+        }
+
+        self.buckets[bucket].push((key, value));
+        self.len += 1;
+
+        if self.load_factor() > MAX_LOAD_FACTOR {
+            self.resize(2 * self.buckets.len());
+        }
+
+        None
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+
+        self.buckets[self.bucket(key)]
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+
+        let bucket = self.bucket(key);
+
+        self.buckets[bucket]
+            .iter_mut()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+
+        let bucket = self.bucket(key);
+        let pos = self.buckets[bucket].iter().position(|(k, _)| k == key)?;
+
+        self.len -= 1;
+
+        // the order in a chain is irrelevant
+        Some(self.buckets[bucket].swap_remove(pos).1)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    /// The probe length of an entry is its position in the chain, plus one.
+    fn stats(&self) -> Stats {
+        let probe_lengths = self.buckets.iter().flat_map(|chain| 1..=chain.len());
+
+        Stats::new(self.capacity(), self.resizes, probe_lengths)
+    }
+}
@@ -0,0 +1,171 @@
+//! Cuckoo hashing with two tables.
+//!
+//! Each key has one candidate slot in each table, given by two independent hash functions, and it's always
+//! stored in one of them: a lookup or a removal inspects at most two slots in the worst case.
+//!
+//! An insertion puts the key in its slot of the first table. If the slot is taken, the resident key is kicked
+//! out and moved to its slot in the other table, possibly kicking out another key, and so on. If this goes on
+//! for more than `MAX_KICKS` steps there is probably a cycle, so the tables are rebuilt with new hash functions.
+//! The expected insertion time is O(1) as long as the load factor stays below 1/2.
+
+use std::hash::Hash;
+
+use super::{hash_with_seed, Map, Stats, INITIAL_CAPACITY};
+
+const MAX_LOAD_FACTOR: f64 = 0.5;
+const MAX_KICKS: usize = 64;
+
+pub struct CuckooMap<K, V> {
+    tables: [Vec<Option<(K, V)>>; 2],
+    /// Seeds of the two hash functions, changed on every rebuild.
+    seeds: [u64; 2],
+    len: usize,
+    resizes: usize,
+}
+
+impl<K: Hash + Eq, V> Default for CuckooMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> CuckooMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            tables: [Vec::new(), Vec::new()],
+            seeds: [1, 2],
+            len: 0,
+            resizes: 0,
+        }
+    }
+
+    fn slot(&self, table: usize, key: &K) -> usize {
+        hash_with_seed(key, self.seeds[table]) as usize & (self.tables[table].len() - 1)
+    }
+
+    /// Table and slot holding `key`.
+    fn find(&self, key: &K) -> Option<(usize, usize)> {
+        if self.tables[0].is_empty() {
+            return None;
+        }
+
+        (0..2)
+            .map(|t| (t, self.slot(t, key)))
+            .find(|&(t, s)| matches!(&self.tables[t][s], Some((k, _)) if k == key))
+    }
+
+    /// Places a key that is not in the table. On failure the key left without a slot is returned.
+    fn place(&mut self, mut entry: (K, V)) -> Result<(), (K, V)> {
+        for kick in 0..MAX_KICKS {
+            let table = kick % 2;
+            let slot = self.slot(table, &entry.0);
+
+            match self.tables[table][slot].replace(entry) {
+                None => return Ok(()),
+                Some(evicted) => entry = evicted,
+            }
+        }
+
+        Err(entry)
+    }
+
+    /// Rebuilds both tables with `capacity` slots each and new hash functions, then places `pending`.
+    fn rebuild(&mut self, capacity: usize, pending: (K, V)) {
+        let mut capacity = capacity;
+        let mut entries: Vec<_> = self
+            .tables
+            .iter_mut()
+            .flat_map(|table| table.drain(..))
+            .flatten()
+            .collect();
+
+        entries.push(pending);
+
+        'rebuild: loop {
+            self.resizes += 1;
+            self.seeds = [self.seeds[0].wrapping_add(2), self.seeds[1].wrapping_add(2)];
+            self.tables = [
+                (0..capacity).map(|_| None).collect(),
+                (0..capacity).map(|_| None).collect(),
+            ];
+
+            while let Some(entry) = entries.pop() {
+                if let Err(entry) = self.place(entry) {
+                    // very unlikely with a low load factor: start over with bigger tables
+                    entries.push(entry);
+                    entries.extend(
+                        self.tables
+                            .iter_mut()
+                            .flat_map(|table| table.drain(..))
+                            .flatten(),
+                    );
+                    capacity *= 2;
+
+                    continue 'rebuild;
+                }
+            }
+
+            return;
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> Map<K, V> for CuckooMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some((table, slot)) = self.find(&key) {
+            let (_, old) = self.tables[table][slot].as_mut().unwrap();
+            return Some(std::mem::replace(old, value));
+        }
+
+        self.len += 1;
+
+        if self.tables[0].is_empty() {
+            self.rebuild(INITIAL_CAPACITY / 2, (key, value));
+        } else if self.len as f64 > MAX_LOAD_FACTOR * self.capacity() as f64 {
+            self.rebuild(2 * self.tables[0].len(), (key, value));
+        } else if let Err(entry) = self.place((key, value)) {
+            self.rebuild(self.tables[0].len(), entry);
+        }
+
+        None
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let (table, slot) = self.find(key)?;
+
+        self.tables[table][slot].as_ref().map(|(_, v)| v)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let (table, slot) = self.find(key)?;
+
+        self.tables[table][slot].as_mut().map(|(_, v)| v)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let (table, slot) = self.find(key)?;
+
+        self.len -= 1;
+
+        self.tables[table][slot].take().map(|(_, v)| v)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.tables[0].len() + self.tables[1].len()
+    }
+
+    /// The probe length of an entry is 1 in the first table and 2 in the second one.
+    fn stats(&self) -> Stats {
+        let probe_lengths = self
+            .tables
+            .iter()
+            .enumerate()
+            .flat_map(|(t, table)| table.iter().flatten().map(move |_| t + 1));
+
+        Stats::new(self.capacity(), self.resizes, probe_lengths)
+    }
+}
@@ -0,0 +1,191 @@
+//! Open addressing with linear probing.
+//!
+//! A key is stored in the first free slot starting from its home slot `h(k)`: `h(k)`, `h(k) + 1`, ...
+//! A lookup scans the same sequence until it finds the key or an empty slot. Removed entries are replaced by
+//! tombstones, which lookups skip and insertions reuse, since emptying the slot would cut the probe sequence
+//! of the keys stored after it.
+//!
+//! Clusters of occupied slots grow quickly as the table fills up, so the table is rebuilt when occupied slots
+//! and tombstones exceed `MAX_LOAD_FACTOR`.
+
+use std::hash::Hash;
+
+use super::{hash_with_seed, Map, Stats, INITIAL_CAPACITY};
+
+const MAX_LOAD_FACTOR: f64 = 0.7;
+
+enum Slot<K, V> {
+    Empty,
+    Tombstone,
+    Full(K, V),
+}
+
+pub struct LinearProbingMap<K, V> {
+    slots: Vec<Slot<K, V>>,
+    len: usize,
+    tombstones: usize,
+    resizes: usize,
+}
+
+impl<K: Hash + Eq, V> Default for LinearProbingMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> LinearProbingMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            tombstones: 0,
+            resizes: 0,
+        }
+    }
+
+    fn home(&self, key: &K) -> usize {
+        hash_with_seed(key, 0) as usize & (self.slots.len() - 1)
+    }
+
+    fn next(&self, slot: usize) -> usize {
+        (slot + 1) & (self.slots.len() - 1)
+    }
+
+    /// Index of the slot holding `key`.
+    fn find(&self, key: &K) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+
+        let mut slot = self.home(key);
+
+        // the load factor guarantees that there's an empty slot
+        loop {
+            match &self.slots[slot] {
+                Slot::Empty => return None,
+                Slot::Full(k, _) if k == key => return Some(slot),
+                _ => slot = self.next(slot),
+            }
+        }
+    }
+
+    /// Rebuilds the table, dropping all the tombstones.
+    fn resize(&mut self, capacity: usize) {
+        let old = std::mem::replace(
+            &mut self.slots,
+            (0..capacity).map(|_| Slot::Empty).collect(),
+        );
+
+        self.tombstones = 0;
+
+        for slot in old {
+            if let Slot::Full(key, value) = slot {
+                let mut idx = self.home(&key);
+
+                while let Slot::Full(..) = self.slots[idx] {
+                    idx = self.next(idx);
+                }
+
+                self.slots[idx] = Slot::Full(key, value);
+            }
+        }
+
+        self.resizes += 1;
+    }
+}
+
+impl<K: Hash + Eq, V> Map<K, V> for LinearProbingMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(slot) = self.find(&key) {
+            if let Slot::Full(_, old) = &mut self.slots[slot] {
+                return Some(std::mem::replace(old, value));
+            }
+        }
+
+        let used = self.len + self.tombstones + 1;
+
+        if self.slots.is_empty() {
+            self.resize(INITIAL_CAPACITY);
+        } else if used as f64 > MAX_LOAD_FACTOR * self.slots.len() as f64 {
+            // only grow if the live entries need it, otherwise clearing the tombstones is enough
+            let capacity =
+                if (self.len + 1) as f64 > MAX_LOAD_FACTOR * self.slots.len() as f64 / 2.0 {
+                    2 * self.slots.len()
+                } else {
+                    self.slots.len()
+                };
+
+            self.resize(capacity);
+        }
+
+        let mut slot = self.home(&key);
+
+        loop {
+            match self.slots[slot] {
+                Slot::Full(..) => slot = self.next(slot),
+                Slot::Tombstone => {
+                    self.tombstones -= 1;
+                    break;
+                }
+                Slot::Empty => break,
+            }
+        }
+
+        self.slots[slot] = Slot::Full(key, value);
+        self.len += 1;
+
+        None
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        match &self.slots[self.find(key)?] {
+            Slot::Full(_, value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let slot = self.find(key)?;
+
+        match &mut self.slots[slot] {
+            Slot::Full(_, value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let slot = self.find(key)?;
+
+        match std::mem::replace(&mut self.slots[slot], Slot::Tombstone) {
+            Slot::Full(_, value) => {
+                self.len -= 1;
+                self.tombstones += 1;
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The probe length of an entry is its distance from the home slot, plus one.
+    fn stats(&self) -> Stats {
+        let capacity = self.capacity();
+        let probe_lengths = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, slot)| match slot {
+                Slot::Full(key, _) => Some((idx + capacity - self.home(key)) % capacity + 1),
+                _ => None,
+            });
+
+        Stats::new(capacity, self.resizes, probe_lengths)
+    }
+}
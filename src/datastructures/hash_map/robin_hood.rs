@@ -0,0 +1,187 @@
+//! Robin Hood hashing: linear probing where "rich" entries give their slot to "poor" ones.
+//!
+//! Every entry records its probe distance, i.e. how far it is from its home slot. While probing for a free slot,
+//! the entry being inserted swaps place with any entry closer to its home than the inserted one is to its own,
+//! and the insertion continues with the evicted entry. This keeps the variance of the probe lengths low, and a
+//! lookup can stop as soon as it meets an entry with a smaller distance than the one probed so far.
+//!
+//! Removal shifts the following entries one slot back until an empty slot or an entry in its home slot, so no
+//! tombstones are needed. Thanks to the short probe sequences the table can be filled up to `MAX_LOAD_FACTOR`.
+
+use std::hash::Hash;
+
+use super::{hash_with_seed, Map, Stats, INITIAL_CAPACITY};
+
+const MAX_LOAD_FACTOR: f64 = 0.9;
+
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    /// Distance from the home slot.
+    distance: usize,
+}
+
+pub struct RobinHoodMap<K, V> {
+    slots: Vec<Option<Entry<K, V>>>,
+    len: usize,
+    resizes: usize,
+}
+
+impl<K: Hash + Eq, V> Default for RobinHoodMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> RobinHoodMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            len: 0,
+            resizes: 0,
+        }
+    }
+
+    fn home(&self, key: &K) -> usize {
+        hash_with_seed(key, 0) as usize & (self.slots.len() - 1)
+    }
+
+    fn next(&self, slot: usize) -> usize {
+        (slot + 1) & (self.slots.len() - 1)
+    }
+
+    fn find(&self, key: &K) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+
+        let mut slot = self.home(key);
+        let mut distance = 0;
+
+        while let Some(entry) = &self.slots[slot] {
+            // the key would have taken this slot if it was in the table
+            if entry.distance < distance {
+                return None;
+            }
+
+            if entry.key == *key {
+                return Some(slot);
+            }
+
+            slot = self.next(slot);
+            distance += 1;
+        }
+
+        None
+    }
+
+    /// Places a key that is not in the table yet, displacing richer entries.
+    fn place(&mut self, mut entry: Entry<K, V>) {
+        let mut slot = self.home(&entry.key);
+        entry.distance = 0;
+
+        loop {
+            match &mut self.slots[slot] {
+                None => {
+                    self.slots[slot] = Some(entry);
+                    return;
+                }
+                Some(resident) => {
+                    if resident.distance < entry.distance {
+                        std::mem::swap(resident, &mut entry);
+                    }
+                }
+            }
+
+            slot = self.next(slot);
+            entry.distance += 1;
+        }
+    }
+
+    fn resize(&mut self, capacity: usize) {
+        let old = std::mem::replace(&mut self.slots, (0..capacity).map(|_| None).collect());
+
+        for entry in old.into_iter().flatten() {
+            self.place(entry);
+        }
+
+        self.resizes += 1;
+    }
+}
+
+impl<K: Hash + Eq, V> Map<K, V> for RobinHoodMap<K, V> {
+    fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(slot) = self.find(&key) {
+            let entry = self.slots[slot].as_mut().unwrap();
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+
+        if self.slots.is_empty() {
+            self.resize(INITIAL_CAPACITY);
+        } else if (self.len + 1) as f64 > MAX_LOAD_FACTOR * self.slots.len() as f64 {
+            self.resize(2 * self.slots.len());
+        }
+
+        self.place(Entry {
+            key,
+            value,
+            distance: 0,
+        });
+        self.len += 1;
+
+        None
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        let slot = self.find(key)?;
+
+        self.slots[slot].as_ref().map(|entry| &entry.value)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let slot = self.find(key)?;
+
+        self.slots[slot].as_mut().map(|entry| &mut entry.value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        let mut slot = self.find(key)?;
+        let removed = self.slots[slot].take()?;
+
+        // backward shift: pull the following entries one step closer to their home
+        loop {
+            let next = self.next(slot);
+
+            match self.slots[next].take() {
+                Some(mut entry) if entry.distance > 0 => {
+                    entry.distance -= 1;
+                    self.slots[slot] = Some(entry);
+                    slot = next;
+                }
+                entry => {
+                    self.slots[next] = entry;
+                    break;
+                }
+            }
+        }
+
+        self.len -= 1;
+
+        Some(removed.value)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The probe length of an entry is its distance from the home slot, plus one.
+    fn stats(&self) -> Stats {
+        let probe_lengths = self.slots.iter().flatten().map(|entry| entry.distance + 1);
+
+        Stats::new(self.capacity(), self.resizes, probe_lengths)
+    }
+}
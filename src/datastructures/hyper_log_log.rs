@@ -0,0 +1,152 @@
+//! HyperLogLog: approximate count of the distinct items of a stream.
+//!
+//! The hash of each item is split in two: the first `p` bits select one of `m = 2^p` registers, and the
+//! register keeps the maximum "rank" seen, i.e. the position of the first 1 bit in the remaining bits.
+//! Seeing a rank `r` means that about `2^r` distinct items hashed to that register, and the estimate is the
+//! normalized harmonic mean of those values. The standard error is `1.04 / sqrt(m)`, using `m` bytes.
+//!
+//! Merging two sketches (register-wise maximum) gives the sketch of the union of the two streams.
+
+use std::hash::Hash;
+
+use super::hash_map::hash_with_seed;
+
+const MIN_PRECISION: u32 = 4;
+const MAX_PRECISION: u32 = 18;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyperLogLog {
+    precision: u32,
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    /// Creates a sketch with `2^precision` registers.
+    pub fn new(precision: u32) -> Self {
+        assert!(
+            (MIN_PRECISION..=MAX_PRECISION).contains(&precision),
+            "precision must be between {} and {}",
+            MIN_PRECISION,
+            MAX_PRECISION
+        );
+
+        Self {
+            precision,
+            registers: vec![0; 1 << precision],
+        }
+    }
+
+    /// Creates the smallest sketch whose standard error is at most `relative_error`.
+    pub fn with_error(relative_error: f64) -> Self {
+        assert!(relative_error > 0.0, "invalid error");
+
+        let registers = (1.04 / relative_error).powi(2);
+        let precision = registers.log2().ceil() as u32;
+
+        Self::new(precision.clamp(MIN_PRECISION, MAX_PRECISION))
+    }
+
+    pub fn precision(&self) -> u32 {
+        self.precision
+    }
+
+    /// Standard error of the estimates, `1.04 / sqrt(m)`.
+    pub fn relative_error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) {
+        let hash = hash_with_seed(item, 0);
+        let register = (hash >> (64 - self.precision)) as usize;
+        // a sentinel bit bounds the rank when the remaining bits are all 0
+        let rest = (hash << self.precision) | (1 << (self.precision - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+
+        self.registers[register] = self.registers[register].max(rank);
+    }
+
+    /// Estimated number of distinct items inserted.
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+
+        let sum: f64 = self.registers.iter().map(|&r| 2f64.powi(-(r as i32))).sum();
+        let raw = alpha * m * m / sum;
+
+        // for small cardinalities, linear counting on the empty registers is more accurate
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+
+        if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+
+    /// Turns `self` into the sketch of the union of both streams.
+    ///
+    /// Panics if the sketches have different precisions.
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        assert_eq!(
+            self.precision, other.precision,
+            "cannot merge sketches with different precisions"
+        );
+
+        for (register, &other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(other);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HyperLogLog;
+
+    #[test]
+    fn cardinality() {
+        let mut sketch = HyperLogLog::with_error(0.01);
+
+        assert!(sketch.relative_error() <= 0.01);
+        assert_eq!(0.0, sketch.estimate());
+
+        for round in 0..3 {
+            // duplicates must not change the estimate
+            for i in 0..50_000u64 {
+                sketch.insert(&(i + round % 2));
+            }
+        }
+
+        let estimate = sketch.estimate();
+        assert!(
+            (estimate - 50_001.0).abs() < 0.03 * 50_001.0,
+            "{}",
+            estimate
+        );
+
+        let mut small = HyperLogLog::new(10);
+        (0..100u32).for_each(|i| small.insert(&i));
+        assert!((small.estimate() - 100.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn merge() {
+        let mut a = HyperLogLog::new(12);
+        let mut b = HyperLogLog::new(12);
+
+        (0..20_000u32).for_each(|i| a.insert(&i));
+        (10_000..30_000u32).for_each(|i| b.insert(&i));
+        a.merge(&b);
+
+        let estimate = a.estimate();
+        assert!(
+            (estimate - 30_000.0).abs() < 0.05 * 30_000.0,
+            "{}",
+            estimate
+        );
+    }
+}
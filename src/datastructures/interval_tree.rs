@@ -0,0 +1,283 @@
+//! Interval tree: a map from intervals to values answering "which intervals contain a point" (stabbing) and
+//! "which intervals overlap an interval" queries.
+//!
+//! The intervals are kept in an `AvlTree` ordered by start, augmented with the maximum end of every subtree.
+//! A query skips the subtrees whose maximum end is before the query, and stops at the first interval starting
+//! after it, so it runs in O(log n + k) for `k` results.
+
+use std::iter::FromIterator;
+use std::ops::{Bound, Range};
+
+use super::avl_tree::{self, Augment, AvlTree, Node};
+
+/// Half-open interval `[start, end)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+impl<T: Ord> Interval<T> {
+    pub fn new(start: T, end: T) -> Self {
+        assert!(start <= end, "interval ends before it starts");
+
+        Self { start, end }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, point: &T) -> bool {
+        self.start <= *point && *point < self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+impl<T: Ord> From<Range<T>> for Interval<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+
+/// Augmentation with the maximum end of the intervals in a subtree.
+pub struct MaxEnd;
+
+impl<T: Ord + Clone> Augment<Interval<T>> for MaxEnd {
+    type Summary = T;
+
+    fn summarize(key: &Interval<T>, left: Option<&T>, right: Option<&T>) -> T {
+        let mut max = &key.end;
+
+        for end in left.into_iter().chain(right) {
+            max = max.max(end);
+        }
+
+        max.clone()
+    }
+}
+
+pub struct IntervalTree<T: Ord + Clone, V> {
+    tree: AvlTree<Interval<T>, V, MaxEnd>,
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    pub fn new() -> Self {
+        Self {
+            tree: AvlTree::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+
+    /// Inserts `value` under `interval`, returning the previous value if the interval was present.
+    pub fn insert(&mut self, interval: impl Into<Interval<T>>, value: V) -> Option<V> {
+        self.tree.insert(interval.into(), value)
+    }
+
+    pub fn remove(&mut self, interval: &Interval<T>) -> Option<V> {
+        self.tree.remove(interval)
+    }
+
+    pub fn get(&self, interval: &Interval<T>) -> Option<&V> {
+        self.tree.get(interval)
+    }
+
+    pub fn get_mut(&mut self, interval: &Interval<T>) -> Option<&mut V> {
+        self.tree.get_mut(interval)
+    }
+
+    /// Intervals containing `point`, ordered by start.
+    pub fn stab(&self, point: T) -> Overlapping<'_, T, V> {
+        Overlapping::new(self.tree.root(), point.clone(), Bound::Included(point))
+    }
+
+    /// Intervals overlapping `interval`, ordered by start.
+    pub fn overlapping(&self, interval: impl Into<Interval<T>>) -> Overlapping<'_, T, V> {
+        let Interval { start, end } = interval.into();
+
+        Overlapping::new(self.tree.root(), start, Bound::Excluded(end))
+    }
+
+    /// Whether any interval overlaps `interval`.
+    pub fn overlaps(&self, interval: impl Into<Interval<T>>) -> bool {
+        self.overlapping(interval).next().is_some()
+    }
+
+    /// Iterates over all the intervals, ordered by start.
+    pub fn iter(&self) -> avl_tree::Iter<'_, Interval<T>, V, T> {
+        self.tree.iter()
+    }
+}
+
+impl<'a, T: Ord + Clone, V> IntoIterator for &'a IntervalTree<T, V> {
+    type Item = (&'a Interval<T>, &'a V);
+    type IntoIter = avl_tree::Iter<'a, Interval<T>, V, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord + Clone, V, I: Into<Interval<T>>> FromIterator<(I, V)> for IntervalTree<T, V> {
+    fn from_iter<It: IntoIterator<Item = (I, V)>>(iter: It) -> Self {
+        let mut tree = Self::new();
+
+        for (interval, value) in iter {
+            tree.insert(interval, value);
+        }
+
+        tree
+    }
+}
+
+/// Intervals ending after `low` and starting before `high`, in order.
+pub struct Overlapping<'a, T, V> {
+    /// Nodes whose left subtree has been visited, the next one on top.
+    stack: Vec<&'a Node<Interval<T>, V, T>>,
+    low: T,
+    high: Bound<T>,
+}
+
+impl<'a, T: Ord, V> Overlapping<'a, T, V> {
+    fn new(root: Option<&'a Node<Interval<T>, V, T>>, low: T, high: Bound<T>) -> Self {
+        let mut iter = Self {
+            stack: Vec::new(),
+            low,
+            high,
+        };
+        iter.push_left(root);
+        iter
+    }
+
+    fn push_left(&mut self, mut node: Option<&'a Node<Interval<T>, V, T>>) {
+        while let Some(n) = node {
+            // every interval of the subtree ends before the query
+            if *n.summary() <= self.low {
+                break;
+            }
+
+            self.stack.push(n);
+            node = n.left();
+        }
+    }
+
+    fn starts_after(&self, interval: &Interval<T>) -> bool {
+        match &self.high {
+            Bound::Included(high) => interval.start > *high,
+            Bound::Excluded(high) => interval.start >= *high,
+            Bound::Unbounded => false,
+        }
+    }
+}
+
+impl<'a, T: Ord, V> Iterator for Overlapping<'a, T, V> {
+    type Item = (&'a Interval<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            // the following intervals start even later
+            if self.starts_after(node.key()) {
+                self.stack.clear();
+                return None;
+            }
+
+            self.push_left(node.right());
+
+            if node.key().end > self.low {
+                return Some((node.key(), node.value()));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Interval, IntervalTree};
+    use crate::random::Rng;
+
+    #[test]
+    fn queries() {
+        let mut rng = Rng::new(34);
+
+        // 500 starts out of 1000 repeat some, and the lengths include 0 for empty intervals
+        let intervals: Vec<Interval<u32>> = (0..500)
+            .map(|_| {
+                let start = rng.below(1000) as u32;
+                Interval::new(start, start + rng.below(50) as u32)
+            })
+            .collect();
+
+        let mut tree: IntervalTree<u32, usize> = intervals.iter().copied().zip(0..).collect();
+        let removed: Vec<_> = intervals.iter().copied().step_by(3).collect();
+
+        for interval in &removed {
+            tree.remove(interval);
+        }
+
+        let mut reference: Vec<_> = intervals
+            .into_iter()
+            .filter(|i| !removed.contains(i))
+            .collect();
+        reference.sort();
+        reference.dedup();
+
+        assert_eq!(reference.len(), tree.len());
+        assert!(tree.iter().map(|(i, _)| *i).eq(reference.iter().copied()));
+
+        for point in (0..1060).step_by(7) {
+            let expected = reference.iter().filter(|i| i.contains(&point));
+            assert!(tree.stab(point).map(|(i, _)| i).eq(expected));
+        }
+
+        for start in (0..1060).step_by(13) {
+            let query = Interval::new(start, start + 20);
+            let expected = reference.iter().filter(|i| i.overlaps(&query));
+            assert!(tree.overlapping(query).map(|(i, _)| i).eq(expected));
+        }
+    }
+
+    #[test]
+    fn schedule() {
+        let mut meetings = IntervalTree::new();
+
+        meetings.insert(9..10, "standup");
+        meetings.insert(10..12, "review");
+        meetings.insert(13..17, "workshop");
+
+        assert!(!meetings.overlaps(12..13));
+        assert!(meetings.overlaps(11..14));
+        assert_eq!(
+            vec!["review", "workshop"],
+            meetings
+                .overlapping(11..14)
+                .map(|(_, v)| *v)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![&"review"],
+            meetings.stab(10).map(|(_, v)| v).collect::<Vec<_>>()
+        );
+
+        *meetings.get_mut(&Interval::new(9, 10)).unwrap() = "sync";
+        assert_eq!(Some("sync"), meetings.remove(&(9..10).into()));
+        assert_eq!(0, meetings.stab(9).count());
+    }
+}
@@ -0,0 +1,347 @@
+//! k-d tree: a binary space partitioning of points in `K` dimensions.
+//!
+//! Every node splits space with a hyperplane perpendicular to one axis, cycling through the axes with the depth.
+//! The tree is built in bulk by splitting on the median point, found with a linear time selection, so it is
+//! perfectly balanced and the build takes O(n log n). It is stored implicitly: the median of a slice is its
+//! middle element, with the left subtree before it and the right subtree after it.
+//!
+//! Searches descend to the side of the query first, and only visit the other side of a hyperplane if it is closer
+//! than the current search radius. Nearest neighbor search takes O(log n) on average for well distributed points.
+
+use std::cmp::Ordering;
+
+use super::heap::MaxHeap;
+
+/// A point of the tree with its distance to the query.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor<'a, T, const K: usize> {
+    pub point: &'a [f64; K],
+    pub value: &'a T,
+    pub distance: f64,
+}
+
+/// Candidate of a k-nearest neighbors search, ordered by distance.
+struct Candidate {
+    squared_distance: f64,
+    idx: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.squared_distance
+            .total_cmp(&other.squared_distance)
+            .then(self.idx.cmp(&other.idx))
+    }
+}
+
+fn squared_distance<const K: usize>(a: &[f64; K], b: &[f64; K]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+pub struct KdTree<T, const K: usize> {
+    /// Points in implicit tree order, see the module documentation.
+    entries: Vec<([f64; K], T)>,
+}
+
+impl<const K: usize> KdTree<usize, K> {
+    /// Builds a tree over `points`, with the index of every point as its value.
+    pub fn from_points(points: &[[f64; K]]) -> Self {
+        Self::new(points.iter().copied().zip(0..).collect())
+    }
+}
+
+impl<T, const K: usize> KdTree<T, K> {
+    /// Builds a tree over the points and their values.
+    pub fn new(mut entries: Vec<([f64; K], T)>) -> Self {
+        assert!(K > 0, "zero dimensional points");
+
+        Self::build(&mut entries, 0);
+
+        Self { entries }
+    }
+
+    fn build(entries: &mut [([f64; K], T)], depth: usize) {
+        if entries.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % K;
+        let mid = entries.len() / 2;
+
+        entries.select_nth_unstable_by(mid, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+
+        let (left, right) = entries.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterates over the points and their values, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[f64; K], &T)> {
+        self.entries.iter().map(|(point, value)| (point, value))
+    }
+
+    pub fn nearest(&self, query: &[f64; K]) -> Option<Neighbor<'_, T, K>> {
+        self.k_nearest(query, 1).pop()
+    }
+
+    /// The `k` points closest to `query`, closest first.
+    pub fn k_nearest(&self, query: &[f64; K], k: usize) -> Vec<Neighbor<'_, T, K>> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        // max heap of the best candidates so far, the worst on top
+        let mut best = MaxHeap::new(Vec::with_capacity(k + 1));
+        self.k_nearest_in(0, self.entries.len(), 0, query, k, &mut best);
+
+        let mut neighbors = Vec::with_capacity(best.len());
+
+        while let Some(candidate) = best.extract() {
+            neighbors.push(self.neighbor(candidate.idx, candidate.squared_distance));
+        }
+
+        neighbors.reverse();
+        neighbors
+    }
+
+    fn k_nearest_in(
+        &self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        query: &[f64; K],
+        k: usize,
+        best: &mut MaxHeap<Candidate>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let point = &self.entries[mid].0;
+
+        best.insert(Candidate {
+            squared_distance: squared_distance(point, query),
+            idx: mid,
+        });
+
+        if best.len() > k {
+            best.extract();
+        }
+
+        let diff = query[depth % K] - point[depth % K];
+        let (near, far) = if diff < 0.0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+
+        self.k_nearest_in(near.0, near.1, depth + 1, query, k, best);
+
+        // the other side of the hyperplane may hold closer points than the worst candidate
+        let worst = best.peek().map_or(f64::INFINITY, |c| c.squared_distance);
+
+        if best.len() < k || diff * diff < worst {
+            self.k_nearest_in(far.0, far.1, depth + 1, query, k, best);
+        }
+    }
+
+    /// The points at distance at most `radius` from `query`, closest first.
+    pub fn within_radius(&self, query: &[f64; K], radius: f64) -> Vec<Neighbor<'_, T, K>> {
+        let mut found: Vec<Candidate> = Vec::new();
+        self.within_radius_in(0, self.entries.len(), 0, query, radius * radius, &mut found);
+
+        found.sort_unstable();
+        found
+            .into_iter()
+            .map(|c| self.neighbor(c.idx, c.squared_distance))
+            .collect()
+    }
+
+    fn within_radius_in(
+        &self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        query: &[f64; K],
+        squared_radius: f64,
+        found: &mut Vec<Candidate>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let point = &self.entries[mid].0;
+        let distance = squared_distance(point, query);
+
+        if distance <= squared_radius {
+            found.push(Candidate {
+                squared_distance: distance,
+                idx: mid,
+            });
+        }
+
+        let diff = query[depth % K] - point[depth % K];
+
+        if diff < 0.0 || diff * diff <= squared_radius {
+            self.within_radius_in(lo, mid, depth + 1, query, squared_radius, found);
+        }
+
+        if diff >= 0.0 || diff * diff <= squared_radius {
+            self.within_radius_in(mid + 1, hi, depth + 1, query, squared_radius, found);
+        }
+    }
+
+    /// The points in the axis-aligned box between `min` and `max`, bounds included, in no particular order.
+    pub fn range(&self, min: &[f64; K], max: &[f64; K]) -> Vec<(&[f64; K], &T)> {
+        let mut found = Vec::new();
+        self.range_in(0, self.entries.len(), 0, min, max, &mut found);
+        found
+    }
+
+    fn range_in<'a>(
+        &'a self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        min: &[f64; K],
+        max: &[f64; K],
+        found: &mut Vec<(&'a [f64; K], &'a T)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let (point, value) = &self.entries[mid];
+        let axis = depth % K;
+
+        if (0..K).all(|i| min[i] <= point[i] && point[i] <= max[i]) {
+            found.push((point, value));
+        }
+
+        if min[axis] <= point[axis] {
+            self.range_in(lo, mid, depth + 1, min, max, found);
+        }
+
+        if point[axis] <= max[axis] {
+            self.range_in(mid + 1, hi, depth + 1, min, max, found);
+        }
+    }
+
+    fn neighbor(&self, idx: usize, squared_distance: f64) -> Neighbor<'_, T, K> {
+        let (point, value) = &self.entries[idx];
+
+        Neighbor {
+            point,
+            value,
+            distance: squared_distance.sqrt(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KdTree;
+    use crate::random::Rng;
+
+    /// Points in `[0, 100)^K`.
+    fn points<const K: usize>(rng: &mut Rng, n: usize) -> Vec<[f64; K]> {
+        (0..n)
+            .map(|_| [(); K].map(|_| rng.unit() * 100.0))
+            .collect()
+    }
+
+    fn distance<const K: usize>(a: &[f64; K], b: &[f64; K]) -> f64 {
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| (x - y) * (x - y))
+            .sum::<f64>()
+            .sqrt()
+    }
+
+    #[test]
+    fn nearest_neighbors() {
+        let mut rng = Rng::new(35);
+        let points = points::<3>(&mut rng, 1000);
+        let tree = KdTree::from_points(&points);
+
+        assert_eq!(1000, tree.len());
+
+        // some queries fall outside of the bounding box
+        for query in self::points::<3>(&mut rng, 20)
+            .iter()
+            .map(|p| p.map(|x| x * 1.1 - 5.0))
+        {
+            let mut expected: Vec<_> = (0..points.len()).collect();
+            expected.sort_by(|&a, &b| {
+                distance(&points[a], &query).total_cmp(&distance(&points[b], &query))
+            });
+
+            let neighbors = tree.k_nearest(&query, 5);
+            let found: Vec<_> = neighbors.iter().map(|n| *n.value).collect();
+
+            assert_eq!(expected[..5], found[..]);
+            assert_eq!(distance(&points[found[0]], &query), neighbors[0].distance);
+            assert_eq!(Some(expected[0]), tree.nearest(&query).map(|n| *n.value));
+        }
+
+        assert_eq!(1000, tree.k_nearest(&[0.0; 3], 2000).len());
+        assert!(KdTree::<usize, 2>::from_points(&[])
+            .nearest(&[0.0; 2])
+            .is_none());
+    }
+
+    #[test]
+    fn radius_and_range() {
+        let points = points::<2>(&mut Rng::new(35), 500);
+        let tree = KdTree::from_points(&points);
+        let query = [50.0, 50.0];
+
+        let expected = points
+            .iter()
+            .filter(|p| distance(p, &query) <= 10.0)
+            .count();
+        let within = tree.within_radius(&query, 10.0);
+
+        assert_eq!(expected, within.len());
+        assert!(within.windows(2).all(|w| w[0].distance <= w[1].distance));
+
+        let (min, max) = ([20.0, 30.0], [60.0, 45.0]);
+        let mut found: Vec<_> = tree
+            .range(&min, &max)
+            .into_iter()
+            .map(|(_, &i)| i)
+            .collect();
+        found.sort_unstable();
+
+        let expected: Vec<_> = (0..points.len())
+            .filter(|&i| (0..2).all(|d| min[d] <= points[i][d] && points[i][d] <= max[d]))
+            .collect();
+
+        assert_eq!(expected, found);
+    }
+}
@@ -0,0 +1,941 @@
+//! Doubly linked list with a cursor for editing in the middle of the list.
+//!
+//! The list owns its nodes through raw `NonNull` pointers obtained from `Box::into_raw`.
+//! Every `unsafe` block relies on the same invariants, which all the safe methods preserve:
+//!
+//! - every link points to a live node owned by exactly this list;
+//! - `head.prev` and `tail.next` are `None`, and `a.next == b` iff `b.prev == a`;
+//! - `len` is the number of nodes reachable from `head`.
+//!
+//! The tests also run under Miri in CI, which checks the unsafe code for undefined behaviour:
+//! `cargo +nightly miri test -- datastructures::linked_list`.
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+
+type Link<T> = Option<NonNull<Node<T>>>;
+
+struct Node<T> {
+    value: T,
+    prev: Link<T>,
+    next: Link<T>,
+}
+
+impl<T> Node<T> {
+    fn boxed(value: T) -> NonNull<Node<T>> {
+        let node = Box::new(Node {
+            value,
+            prev: None,
+            next: None,
+        });
+
+        // SAFETY: `Box::into_raw` never returns a null pointer.
+        unsafe { NonNull::new_unchecked(Box::into_raw(node)) }
+    }
+}
+
+pub struct LinkedList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+// SAFETY: the list owns its `T`s exactly like a `Vec<T>` would.
+unsafe impl<T: Send> Send for LinkedList<T> {}
+unsafe impl<T: Sync> Sync for LinkedList<T> {}
+
+impl<T> Default for LinkedList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> LinkedList<T> {
+    pub fn new() -> Self {
+        Self {
+            head: None,
+            tail: None,
+            len: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn front(&self) -> Option<&T> {
+        // SAFETY: `head` is a live node owned by `self`, borrowed for as long as `self` is.
+        self.head.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn front_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: as in `front`, and `&mut self` guarantees exclusive access.
+        self.head.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn back(&self) -> Option<&T> {
+        // SAFETY: see `front`.
+        self.tail.map(|node| unsafe { &(*node.as_ptr()).value })
+    }
+
+    pub fn back_mut(&mut self) -> Option<&mut T> {
+        // SAFETY: see `front_mut`.
+        self.tail.map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    /// Adds `value` at the front of the list in O(1).
+    pub fn push_front(&mut self, value: T) {
+        // SAFETY: the node is fresh and `(None, head)` are adjacent.
+        unsafe { self.link(Node::boxed(value), None, self.head) }
+    }
+
+    /// Adds `value` at the back of the list in O(1).
+    pub fn push_back(&mut self, value: T) {
+        // SAFETY: the node is fresh and `(tail, None)` are adjacent.
+        unsafe { self.link(Node::boxed(value), self.tail, None) }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        // SAFETY: `head` belongs to this list.
+        self.head.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn pop_back(&mut self) -> Option<T> {
+        // SAFETY: `tail` belongs to this list.
+        self.tail.map(|node| unsafe { self.unlink(node) })
+    }
+
+    pub fn clear(&mut self) {
+        while self.pop_front().is_some() {}
+    }
+
+    /// Moves all the elements of `other` to the back of `self` in O(1), leaving `other` empty.
+    pub fn append(&mut self, other: &mut LinkedList<T>) {
+        let other = std::mem::take(other);
+
+        // SAFETY: `(tail, None)` are adjacent in `self`.
+        unsafe { self.splice(self.tail, None, other) }
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|v| v == value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            head: self.head,
+            tail: self.tail,
+            len: self.len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a cursor pointing at the first element (or at the "ghost" position if empty).
+    pub fn cursor_front_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            index: 0,
+            current: self.head,
+            list: self,
+        }
+    }
+
+    /// Returns a cursor pointing at the last element (or at the "ghost" position if empty).
+    pub fn cursor_back_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut {
+            index: self.len.saturating_sub(1),
+            current: self.tail,
+            list: self,
+        }
+    }
+
+    /// Links `node` between `prev` and `next`, where `None` stands for the respective end.
+    ///
+    /// # Safety
+    /// `node` must be unlinked and not owned by any list. `prev` and `next` must be adjacent in `self`.
+    unsafe fn link(&mut self, node: NonNull<Node<T>>, prev: Link<T>, next: Link<T>) {
+        (*node.as_ptr()).prev = prev;
+        (*node.as_ptr()).next = next;
+
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(node),
+            None => self.head = Some(node),
+        }
+
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(node),
+            None => self.tail = Some(node),
+        }
+
+        self.len += 1;
+    }
+
+    /// Unlinks `node` from `self`, frees it and returns its value.
+    ///
+    /// # Safety
+    /// `node` must belong to `self`.
+    unsafe fn unlink(&mut self, node: NonNull<Node<T>>) -> T {
+        let node = Box::from_raw(node.as_ptr());
+
+        match node.prev {
+            Some(prev) => (*prev.as_ptr()).next = node.next,
+            None => self.head = node.next,
+        }
+
+        match node.next {
+            Some(next) => (*next.as_ptr()).prev = node.prev,
+            None => self.tail = node.prev,
+        }
+
+        self.len -= 1;
+
+        node.value
+    }
+
+    /// Moves every node of `other` between `prev` and `next` in O(1).
+    ///
+    /// # Safety
+    /// `prev` and `next` must be adjacent in `self`.
+    unsafe fn splice(&mut self, prev: Link<T>, next: Link<T>, mut other: LinkedList<T>) {
+        let (first, last) = match (other.head.take(), other.tail.take()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return,
+        };
+
+        (*first.as_ptr()).prev = prev;
+        (*last.as_ptr()).next = next;
+
+        match prev {
+            Some(prev) => (*prev.as_ptr()).next = Some(first),
+            None => self.head = Some(first),
+        }
+
+        match next {
+            Some(next) => (*next.as_ptr()).prev = Some(last),
+            None => self.tail = Some(last),
+        }
+
+        self.len += std::mem::replace(&mut other.len, 0);
+    }
+}
+
+impl<T> Drop for LinkedList<T> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T: Clone> Clone for LinkedList<T> {
+    fn clone(&self) -> Self {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for LinkedList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for LinkedList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for LinkedList<T> {}
+
+impl<T> FromIterator<T> for LinkedList<T> {
+    fn from_iter<It: IntoIterator<Item = T>>(iter: It) -> Self {
+        let mut list = Self::new();
+        list.extend(iter);
+        list
+    }
+}
+
+impl<T> Extend<T> for LinkedList<T> {
+    fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
+        iter.into_iter().for_each(|value| self.push_back(value));
+    }
+}
+
+/// A cursor over a `LinkedList` that can edit the list around its position.
+///
+/// Besides the elements, the cursor can point at a "ghost" position between the tail and the head,
+/// so that it can move around the list in a cycle. All the operations are O(1).
+pub struct CursorMut<'a, T> {
+    index: usize,
+    current: Link<T>,
+    list: &'a mut LinkedList<T>,
+}
+
+impl<'a, T> CursorMut<'a, T> {
+    /// Index of the current element, `None` if the cursor is at the ghost position.
+    pub fn index(&self) -> Option<usize> {
+        self.current.map(|_| self.index)
+    }
+
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: `current` belongs to the list, which is borrowed mutably by the cursor.
+        self.current
+            .map(|node| unsafe { &mut (*node.as_ptr()).value })
+    }
+
+    pub fn peek_next(&mut self) -> Option<&mut T> {
+        // SAFETY: see `current`.
+        unsafe {
+            let next = match self.current {
+                Some(node) => (*node.as_ptr()).next,
+                None => self.list.head,
+            };
+
+            next.map(|node| &mut (*node.as_ptr()).value)
+        }
+    }
+
+    pub fn peek_prev(&mut self) -> Option<&mut T> {
+        // SAFETY: see `current`.
+        unsafe {
+            let prev = match self.current {
+                Some(node) => (*node.as_ptr()).prev,
+                None => self.list.tail,
+            };
+
+            prev.map(|node| &mut (*node.as_ptr()).value)
+        }
+    }
+
+    /// Moves to the next element. From the tail it moves to the ghost position, and from there to the head.
+    pub fn move_next(&mut self) {
+        match self.current {
+            Some(node) => {
+                // SAFETY: `node` belongs to the list.
+                self.current = unsafe { (*node.as_ptr()).next };
+                self.index += 1;
+            }
+            None => {
+                self.current = self.list.head;
+                self.index = 0;
+            }
+        }
+    }
+
+    /// Moves to the previous element. From the head it moves to the ghost position, and from there to the tail.
+    pub fn move_prev(&mut self) {
+        match self.current {
+            Some(node) => {
+                // SAFETY: `node` belongs to the list.
+                self.current = unsafe { (*node.as_ptr()).prev };
+                self.index = match self.current {
+                    Some(_) => self.index - 1,
+                    None => self.list.len,
+                };
+            }
+            None => {
+                self.current = self.list.tail;
+                self.index = self.list.len.saturating_sub(1);
+            }
+        }
+    }
+
+    /// Inserts `value` before the current element. At the ghost position it is inserted at the back.
+    pub fn insert_before(&mut self, value: T) {
+        // SAFETY: `prev` and `current` are adjacent, the node is fresh.
+        unsafe {
+            let prev = self.prev_link();
+            self.list.link(Node::boxed(value), prev, self.current);
+        }
+
+        self.index += 1;
+    }
+
+    /// Inserts `value` after the current element. At the ghost position it is inserted at the front.
+    pub fn insert_after(&mut self, value: T) {
+        // SAFETY: `current` and `next` are adjacent, the node is fresh.
+        unsafe {
+            let next = self.next_link();
+            self.list.link(Node::boxed(value), self.current, next);
+        }
+
+        if self.current.is_none() {
+            // the ghost position is always at index `len`
+            self.index += 1;
+        }
+    }
+
+    /// Removes the current element and returns it. The cursor then points at the following element.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let node = self.current?;
+
+        // SAFETY: `node` belongs to the list. The cursor is moved away before it gets freed.
+        unsafe {
+            self.current = (*node.as_ptr()).next;
+            Some(self.list.unlink(node))
+        }
+    }
+
+    /// Moves every element of `other` before the current element in O(1).
+    pub fn splice_before(&mut self, other: LinkedList<T>) {
+        let moved = other.len;
+
+        // SAFETY: `prev` and `current` are adjacent.
+        unsafe {
+            let prev = self.prev_link();
+            self.list.splice(prev, self.current, other);
+        }
+
+        self.index += moved;
+    }
+
+    /// Moves every element of `other` after the current element in O(1).
+    pub fn splice_after(&mut self, other: LinkedList<T>) {
+        let moved = other.len;
+
+        // SAFETY: `current` and `next` are adjacent.
+        unsafe {
+            let next = self.next_link();
+            self.list.splice(self.current, next, other);
+        }
+
+        if self.current.is_none() {
+            self.index += moved;
+        }
+    }
+
+    /// Splits the list after the current element and returns the tail part.
+    /// At the ghost position the whole list is returned.
+    pub fn split_after(&mut self) -> LinkedList<T> {
+        let mut split = LinkedList::new();
+
+        let first = match self.next_link() {
+            Some(first) => first,
+            None => return split,
+        };
+
+        let moved = match self.current {
+            Some(_) => self.list.len - self.index - 1,
+            None => self.list.len,
+        };
+
+        // SAFETY: `first` up to `tail` is a chain of nodes of the list, which is detached from it as a whole.
+        unsafe {
+            match self.current {
+                Some(node) => (*node.as_ptr()).next = None,
+                None => self.list.head = None,
+            }
+
+            (*first.as_ptr()).prev = None;
+        }
+
+        split.head = Some(first);
+        split.tail = self.list.tail.take();
+        split.len = moved;
+
+        self.list.tail = self.current;
+        self.list.len -= moved;
+
+        if self.current.is_none() {
+            self.index = 0;
+        }
+
+        split
+    }
+
+    fn prev_link(&self) -> Link<T> {
+        match self.current {
+            // SAFETY: `node` belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).prev },
+            None => self.list.tail,
+        }
+    }
+
+    fn next_link(&self) -> Link<T> {
+        match self.current {
+            // SAFETY: `node` belongs to the list.
+            Some(node) => unsafe { (*node.as_ptr()).next },
+            None => self.list.head,
+        }
+    }
+}
+
+pub struct Iter<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: the list is borrowed for `'a` and `len` stops us before reaching yielded nodes.
+        self.head.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.head = node.next;
+            &node.value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: see `next`.
+        self.tail.map(|node| unsafe {
+            let node = &*node.as_ptr();
+            self.len -= 1;
+            self.tail = node.prev;
+            &node.value
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for Iter<'a, T> {}
+
+pub struct IterMut<'a, T> {
+    head: Link<T>,
+    tail: Link<T>,
+    len: usize,
+    _marker: PhantomData<&'a mut Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: the list is mutably borrowed for `'a` and each node is yielded at most once,
+        // so the returned references never alias.
+        self.head.map(|node| unsafe {
+            let node = node.as_ptr();
+            self.len -= 1;
+            self.head = (*node).next;
+            &mut (*node).value
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for IterMut<'a, T> {
+    fn next_back(&mut self) -> Option<&'a mut T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        // SAFETY: see `next`.
+        self.tail.map(|node| unsafe {
+            let node = node.as_ptr();
+            self.len -= 1;
+            self.tail = (*node).prev;
+            &mut (*node).value
+        })
+    }
+}
+
+impl<'a, T> ExactSizeIterator for IterMut<'a, T> {}
+
+pub struct IntoIter<T>(LinkedList<T>);
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.0.pop_front()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.0.len, Some(self.0.len))
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<T> {
+        self.0.pop_back()
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+impl<T> IntoIterator for LinkedList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a LinkedList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut LinkedList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::LinkedList;
+    use crate::random::Rng;
+
+    fn to_vec(list: &LinkedList<i32>) -> Vec<i32> {
+        list.iter().copied().collect()
+    }
+
+    #[test]
+    fn push_pop() {
+        let mut list = LinkedList::new();
+
+        assert_eq!(None, list.pop_front());
+
+        list.push_back(2);
+        list.push_back(3);
+        list.push_front(1);
+
+        assert_eq!(3, list.len());
+        assert_eq!(Some(&1), list.front());
+        assert_eq!(Some(&3), list.back());
+        assert_eq!(
+            vec![3, 2, 1],
+            list.iter().rev().copied().collect::<Vec<_>>()
+        );
+
+        assert_eq!(Some(3), list.pop_back());
+        assert_eq!(Some(1), list.pop_front());
+        assert_eq!(Some(2), list.pop_front());
+        assert_eq!(None, list.pop_back());
+        assert!(list.is_empty());
+
+        let mut list: LinkedList<_> = (0..5).collect();
+        list.iter_mut().for_each(|v| *v *= 10);
+
+        assert_eq!(
+            vec![0, 10, 20, 30, 40],
+            list.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn cursor() {
+        let mut list: LinkedList<_> = (1..=5).collect();
+        let mut cursor = list.cursor_front_mut();
+
+        cursor.move_next();
+        assert_eq!(Some(&mut 2), cursor.current());
+        assert_eq!(Some(1), cursor.index());
+
+        cursor.insert_before(10);
+        cursor.insert_after(20);
+        assert_eq!(Some(2), cursor.index());
+        assert_eq!(Some(2), cursor.remove_current());
+        assert_eq!(Some(&mut 20), cursor.current());
+
+        // walk to the ghost position and wrap around
+        for _ in 0..4 {
+            cursor.move_next();
+        }
+
+        assert_eq!(None, cursor.current());
+        assert_eq!(None, cursor.index());
+
+        cursor.move_next();
+        assert_eq!(Some(&mut 1), cursor.current());
+
+        cursor.move_prev();
+        cursor.insert_after(0);
+        cursor.insert_before(6);
+
+        assert_eq!(vec![0, 1, 10, 20, 3, 4, 5, 6], to_vec(&list));
+        assert_eq!(Some(&6), list.back());
+    }
+
+    #[test]
+    fn splice_and_split() {
+        let mut list: LinkedList<_> = vec![1, 5].into_iter().collect();
+        let mut cursor = list.cursor_front_mut();
+
+        cursor.splice_after((2..5).collect());
+        assert_eq!(Some(0), cursor.index());
+
+        cursor.move_next();
+        cursor.splice_before(LinkedList::new());
+        assert_eq!(Some(&mut 2), cursor.current());
+
+        let tail = cursor.split_after();
+
+        assert_eq!(vec![3, 4, 5], to_vec(&tail));
+        assert_eq!(vec![1, 2], to_vec(&list));
+
+        let mut cursor = list.cursor_back_mut();
+        cursor.move_next();
+        cursor.splice_before(tail);
+
+        assert_eq!(vec![1, 2, 3, 4, 5], to_vec(&list));
+
+        let mut other: LinkedList<_> = (6..8).collect();
+        list.append(&mut other);
+
+        assert!(other.is_empty());
+        assert_eq!(7, list.len());
+        assert_eq!(
+            vec![7, 6, 5, 4, 3, 2, 1],
+            list.iter().rev().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn ghost_edits() {
+        let mut list = LinkedList::new();
+        let mut cursor = list.cursor_front_mut();
+
+        assert_eq!(None, cursor.current());
+        assert_eq!(None, cursor.peek_next());
+        assert_eq!(None, cursor.remove_current());
+
+        // at the ghost position, before is the back and after is the front
+        cursor.insert_before(2);
+        cursor.insert_after(1);
+        cursor.insert_before(3);
+        assert_eq!(None, cursor.index());
+        assert_eq!(Some(&mut 1), cursor.peek_next());
+        assert_eq!(Some(&mut 3), cursor.peek_prev());
+
+        cursor.move_prev();
+        assert_eq!(Some(2), cursor.index());
+        assert_eq!(Some(&mut 3), cursor.current());
+
+        assert_eq!(vec![1, 2, 3], to_vec(&list));
+        assert_eq!(Some(&3), list.back());
+    }
+
+    #[test]
+    fn remove_only_element() {
+        let mut list = LinkedList::new();
+        list.push_back(1);
+
+        let mut cursor = list.cursor_back_mut();
+        assert_eq!(Some(1), cursor.remove_current());
+        assert_eq!(None, cursor.current());
+        assert_eq!(None, cursor.remove_current());
+
+        cursor.insert_before(2);
+        assert_eq!(vec![2], to_vec(&list));
+        assert_eq!(Some(&2), list.front());
+        assert_eq!(Some(&2), list.back());
+    }
+
+    #[test]
+    fn splice_empty() {
+        let mut list: LinkedList<_> = (1..=2).collect();
+        let mut cursor = list.cursor_front_mut();
+
+        cursor.splice_before(LinkedList::new());
+        cursor.splice_after(LinkedList::new());
+        assert_eq!(Some(0), cursor.index());
+        assert_eq!(vec![1, 2], to_vec(&list));
+
+        // onto an empty list, from an element-less cursor
+        let mut empty = LinkedList::new();
+        empty.cursor_front_mut().splice_after(list);
+        assert_eq!(vec![1, 2], to_vec(&empty));
+        assert_eq!(Some(&2), empty.back());
+
+        let mut empty = LinkedList::new();
+        let mut cursor = empty.cursor_back_mut();
+        cursor.splice_before((1..=3).collect());
+        assert_eq!(None, cursor.index());
+
+        cursor.move_next();
+        assert_eq!(Some(&mut 1), cursor.current());
+        assert_eq!(vec![1, 2, 3], to_vec(&empty));
+        assert_eq!(3, empty.len());
+    }
+
+    #[test]
+    fn split_at_ends() {
+        let mut list: LinkedList<_> = (1..=3).collect();
+        let mut cursor = list.cursor_back_mut();
+
+        assert!(cursor.split_after().is_empty());
+        assert_eq!(Some(2), cursor.index());
+
+        cursor.move_next();
+        let all = cursor.split_after();
+        assert_eq!(None, cursor.index());
+        assert_eq!(None, cursor.peek_prev());
+
+        assert!(list.is_empty());
+        assert_eq!(None, list.back());
+        assert_eq!(vec![1, 2, 3], to_vec(&all));
+        assert_eq!(Some(&3), all.back());
+    }
+
+    #[test]
+    fn drop_partly_consumed() {
+        let counter = Rc::new(());
+        let list: LinkedList<_> = (0..5).map(|_| Rc::clone(&counter)).collect();
+        let mut iter = list.into_iter();
+
+        iter.next();
+        iter.next_back();
+        assert_eq!(3, iter.len());
+        assert_eq!(4, Rc::strong_count(&counter));
+
+        drop(iter);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+
+    /// Random cursor operations, checked against a `Vec` with the ghost position at index `len`.
+    #[test]
+    fn cursor_against_vec() {
+        let mut rng = Rng::new(26);
+        let mut list = LinkedList::new();
+        let mut expected: Vec<i32> = Vec::new();
+        let mut cursor = list.cursor_front_mut();
+        let mut pos = 0;
+        let mut next_value = 0;
+
+        for _ in 0..2000 {
+            let len = expected.len();
+
+            match rng.below(8) {
+                0 => {
+                    cursor.move_next();
+                    pos = if pos == len { 0 } else { pos + 1 };
+                }
+                1 => {
+                    cursor.move_prev();
+                    pos = match pos {
+                        _ if pos == len => len.saturating_sub(1),
+                        0 => len,
+                        _ => pos - 1,
+                    };
+                }
+                2 => {
+                    cursor.insert_before(next_value);
+                    expected.insert(pos, next_value);
+                    pos += 1;
+                }
+                3 => {
+                    cursor.insert_after(next_value);
+
+                    if pos == len {
+                        expected.insert(0, next_value);
+                        pos += 1;
+                    } else {
+                        expected.insert(pos + 1, next_value);
+                    }
+                }
+                4 => {
+                    let removed = (pos < len).then(|| expected.remove(pos));
+                    assert_eq!(removed, cursor.remove_current());
+                }
+                5 | 6 => {
+                    let other: Vec<_> = (next_value..next_value + rng.below(4) as i32).collect();
+                    next_value += other.len() as i32;
+
+                    let at = if rng.below(2) == 0 {
+                        cursor.splice_before(other.iter().copied().collect());
+                        pos
+                    } else {
+                        cursor.splice_after(other.iter().copied().collect());
+                        if pos == len {
+                            0
+                        } else {
+                            pos + 1
+                        }
+                    };
+
+                    if at <= pos {
+                        pos += other.len();
+                    }
+
+                    expected.splice(at..at, other);
+                }
+                _ => {
+                    let split = cursor.split_after();
+                    let at = if pos == len { 0 } else { pos + 1 };
+
+                    assert_eq!(expected.split_off(at), to_vec(&split));
+
+                    if pos == len {
+                        pos = 0;
+                    }
+                }
+            }
+
+            next_value += 1;
+
+            let len = expected.len();
+            assert_eq!((pos < len).then_some(pos), cursor.index());
+            assert_eq!(expected.get(pos).copied(), cursor.current().copied());
+
+            let next = if pos == len { 0 } else { pos + 1 };
+            assert_eq!(expected.get(next).copied(), cursor.peek_next().copied());
+
+            let prev = if pos == len {
+                len.checked_sub(1)
+            } else {
+                pos.checked_sub(1)
+            };
+            assert_eq!(prev.map(|prev| expected[prev]), cursor.peek_prev().copied());
+        }
+
+        assert_eq!(expected, to_vec(&list));
+        assert_eq!(
+            expected.iter().rev().copied().collect::<Vec<_>>(),
+            list.iter().rev().copied().collect::<Vec<_>>()
+        );
+        assert_eq!(expected.len(), list.len());
+    }
+}
@@ -0,0 +1,276 @@
+//! Min-max heap: a double-ended priority queue in an array, like `heap`.
+//!
+//! The levels of the implicit binary tree alternate between min levels (the root's, at even depths) and max
+//! levels. An element on a min level is smaller than all its descendants, and one on a max level is greater, so
+//! the minimum is the root and the maximum is one of its two children.
+//!
+//! An insertion swaps the new element with its parent if it is on the wrong kind of level for it, then moves it up
+//! through its grandparents, which are on the same kind of level. A removal moves the last element to the hole
+//! and trickles it down through the smallest (or greatest) of its children and grandchildren. Both are O(log n),
+//! and building the heap bottom-up from a vector is O(n) like `heap::heapify`.
+
+use std::cmp::Ordering;
+
+/// Whether `idx` is on a min level, i.e. at an even depth.
+fn is_min_level(idx: usize) -> bool {
+    (usize::BITS - (idx + 1).leading_zeros()) % 2 == 1
+}
+
+/// The ordering an element must have relative to its descendants at `idx`.
+fn level_ordering(idx: usize) -> Ordering {
+    if is_min_level(idx) {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// Rearranges the sequence into a min-max heap.
+pub fn heapify<V: Ord>(seq: &mut [V]) {
+    for idx in (0..seq.len() / 2).rev() {
+        trickle_down(seq, idx);
+    }
+}
+
+/// Moves the element at `start` up to its place, used after an insertion at the end.
+pub fn bubble_up<V: Ord>(seq: &mut [V], start: usize) {
+    if start == 0 {
+        return;
+    }
+
+    let parent = (start - 1) / 2;
+    let ordering = level_ordering(start);
+
+    // the element belongs to the levels of the other kind
+    if seq[parent].cmp(&seq[start]) == ordering {
+        seq.swap(start, parent);
+        bubble_up_grandparents(seq, parent, ordering.reverse());
+    } else {
+        bubble_up_grandparents(seq, start, ordering);
+    }
+}
+
+fn bubble_up_grandparents<V: Ord>(seq: &mut [V], start: usize, ordering: Ordering) {
+    let mut cursor = start;
+
+    while cursor > 2 {
+        let grandparent = ((cursor - 1) / 2 - 1) / 2;
+
+        if seq[cursor].cmp(&seq[grandparent]) == ordering {
+            seq.swap(cursor, grandparent);
+            cursor = grandparent;
+        } else {
+            break;
+        }
+    }
+}
+
+/// Moves the element at `start` down to its place, used after a removal.
+pub fn trickle_down<V: Ord>(seq: &mut [V], start: usize) {
+    let ordering = level_ordering(start);
+    let mut cursor = start;
+
+    loop {
+        // the best of the children and grandchildren, which are at most 6 consecutive slots in two runs
+        let children = (2 * cursor + 1..2 * cursor + 3).filter(|&i| i < seq.len());
+        let grandchildren = (4 * cursor + 3..4 * cursor + 7).filter(|&i| i < seq.len());
+        let best = children.chain(grandchildren).reduce(|a, b| {
+            if seq[b].cmp(&seq[a]) == ordering {
+                b
+            } else {
+                a
+            }
+        });
+
+        let best = match best {
+            Some(best) if seq[best].cmp(&seq[cursor]) == ordering => best,
+            _ => break,
+        };
+
+        seq.swap(cursor, best);
+
+        if best <= 2 * cursor + 2 {
+            // a child has no descendants left to check
+            break;
+        }
+
+        // the element now below a grandchild's parent may belong on the other kind of level
+        let parent = (best - 1) / 2;
+
+        if seq[parent].cmp(&seq[best]) == ordering {
+            seq.swap(best, parent);
+        }
+
+        cursor = best;
+    }
+}
+
+pub struct MinMaxHeap<V: Ord> {
+    seq: Vec<V>,
+}
+
+impl<V: Ord> Default for MinMaxHeap<V> {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl<V: Ord> MinMaxHeap<V> {
+    /// Builds the heap in O(n).
+    pub fn new(mut seq: Vec<V>) -> Self {
+        heapify(&mut seq);
+        Self { seq }
+    }
+
+    pub fn len(&self) -> usize {
+        self.seq.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seq.is_empty()
+    }
+
+    pub fn push(&mut self, value: V) {
+        self.seq.push(value);
+        let start = self.seq.len() - 1;
+        bubble_up(&mut self.seq, start);
+    }
+
+    pub fn peek_min(&self) -> Option<&V> {
+        self.seq.first()
+    }
+
+    pub fn peek_max(&self) -> Option<&V> {
+        self.max_idx().map(|idx| &self.seq[idx])
+    }
+
+    pub fn pop_min(&mut self) -> Option<V> {
+        self.remove(0)
+    }
+
+    pub fn pop_max(&mut self) -> Option<V> {
+        self.max_idx().and_then(|idx| self.remove(idx))
+    }
+
+    /// Consumes the heap, returning the elements in heap order.
+    pub fn into_vec(self) -> Vec<V> {
+        self.seq
+    }
+
+    /// Index of the maximum: the root if it's alone, else the greatest of its children.
+    fn max_idx(&self) -> Option<usize> {
+        match self.seq.len() {
+            0 => None,
+            1 => Some(0),
+            2 => Some(1),
+            _ => Some(if self.seq[2] > self.seq[1] { 2 } else { 1 }),
+        }
+    }
+
+    fn remove(&mut self, idx: usize) -> Option<V> {
+        if idx >= self.seq.len() {
+            return None;
+        }
+
+        // replace with last
+        let removed = self.seq.swap_remove(idx);
+
+        if idx < self.seq.len() {
+            trickle_down(&mut self.seq, idx);
+        }
+
+        Some(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_min_level, MinMaxHeap};
+    use crate::random::Rng;
+
+    /// Checks that every element is on the right side of all its descendants.
+    fn check<V: Ord>(seq: &[V]) {
+        for idx in 1..seq.len() {
+            let mut ancestor = idx;
+
+            while ancestor > 0 {
+                ancestor = (ancestor - 1) / 2;
+
+                if is_min_level(ancestor) {
+                    assert!(seq[ancestor] <= seq[idx]);
+                } else {
+                    assert!(seq[ancestor] >= seq[idx]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn levels() {
+        let min_levels: Vec<_> = (0..16).map(is_min_level).collect();
+
+        assert_eq!(
+            vec![true, false, false, true, true, true, true, false],
+            min_levels[..8]
+        );
+        assert!(min_levels[7..15].iter().all(|&min| !min));
+        assert!(min_levels[15]);
+    }
+
+    #[test]
+    fn double_ended() {
+        let mut rng = Rng::new(39);
+        let values: Vec<u32> = (0..500).map(|_| rng.below(263) as u32).collect();
+        let heap = MinMaxHeap::new(values.clone());
+
+        check(&heap.into_vec());
+
+        let mut heap = MinMaxHeap::default();
+        let mut sorted = Vec::new();
+
+        for (i, &value) in values.iter().enumerate() {
+            heap.push(value);
+            sorted.push(value);
+            sorted.sort_unstable();
+
+            if i % 3 == 1 {
+                assert_eq!(sorted.pop(), heap.pop_max());
+            } else if i % 5 == 2 {
+                assert_eq!(Some(sorted.remove(0)), heap.pop_min());
+            }
+
+            assert_eq!(sorted.first(), heap.peek_min());
+            assert_eq!(sorted.last(), heap.peek_max());
+        }
+
+        assert_eq!(sorted.len(), heap.len());
+
+        while !heap.is_empty() {
+            assert_eq!(sorted.pop(), heap.pop_max());
+            assert_eq!(sorted.first(), heap.peek_min());
+        }
+
+        assert_eq!(None, heap.pop_min());
+    }
+
+    #[test]
+    fn bounded_top_k() {
+        let mut top = MinMaxHeap::default();
+
+        // the largest value is repeated and the smallest ones come last
+        for value in [5, 12, 9, 12, 3, 7, 10, 8, 11, 1, 0] {
+            top.push(value);
+
+            if top.len() > 5 {
+                top.pop_min();
+            }
+        }
+
+        let mut largest = Vec::new();
+        while let Some(value) = top.pop_max() {
+            largest.push(value);
+        }
+
+        assert_eq!(vec![12, 12, 11, 10, 9], largest);
+    }
+}
@@ -0,0 +1,115 @@
+//! Persistent data structures: updates return a new version and leave the old one untouched and usable.
+//!
+//! The versions share most of their structure through reference counted (`Arc`) nodes, so that an update only
+//! copies the nodes on the path to the change, and cloning a version is O(1):
+//!
+//! - `PersistentStack`: a singly linked list, pushing and popping share the whole tail.
+//! - `PersistentMap`: an AVL tree, an update copies the O(log n) nodes from the root to the change.
+//! - `PersistentVector`: an array mapped trie with 32 children per node, an update copies O(log32 n) nodes.
+//!
+//! `Arc` rather than `Rc` lets versions be sent to other threads, e.g. to snapshot the state of an algorithm.
+
+pub mod map;
+pub mod stack;
+pub mod vector;
+
+pub use map::PersistentMap;
+pub use stack::PersistentStack;
+pub use vector::PersistentVector;
+
+#[cfg(test)]
+mod tests {
+    use super::{PersistentMap, PersistentStack, PersistentVector};
+    use crate::random::Rng;
+
+    #[test]
+    fn stack_versions() {
+        let empty = PersistentStack::new();
+        let one = empty.push(1);
+        let two = one.push(2);
+        let other = one.push(3);
+
+        assert!(empty.is_empty());
+        assert_eq!(vec![&2, &1], two.iter().collect::<Vec<_>>());
+        assert_eq!(vec![&3, &1], other.iter().collect::<Vec<_>>());
+        assert_eq!(Some(&2), two.peek());
+        assert_eq!(one, two.pop().unwrap());
+        assert_eq!(2, other.len());
+        assert!(empty.pop().is_none());
+
+        // dropping a long list must not overflow the stack
+        let long = (0..200_000).fold(PersistentStack::new(), |stack, i| stack.push(i));
+        assert_eq!(200_000, long.len());
+    }
+
+    #[test]
+    fn map_versions() {
+        let mut rng = Rng::new(36);
+        let keys: Vec<u32> = (0..500).map(|_| rng.below(300) as u32).collect();
+        let mut versions = vec![PersistentMap::new()];
+
+        for (i, &key) in (0..).zip(&keys) {
+            let last = versions.last().unwrap();
+
+            versions.push(if i % 4 == 3 {
+                last.remove(&key)
+            } else {
+                last.insert(key, i)
+            });
+        }
+
+        // replay the history on a mutable map to check every version
+        let mut reference = std::collections::BTreeMap::new();
+
+        for ((i, &key), version) in (0..).zip(&keys).zip(&versions[1..]) {
+            if i % 4 == 3 {
+                reference.remove(&key);
+            } else {
+                reference.insert(key, i);
+            }
+
+            assert_eq!(reference.len(), version.len());
+            assert!(version.iter().eq(reference.iter()));
+        }
+
+        let last = versions.last().unwrap();
+        assert_eq!(reference.get(&42), last.get(&42));
+        assert!(last.height() as f64 <= 1.44 * (last.len() as f64 + 2.0).log2());
+        assert!(versions[0].is_empty());
+    }
+
+    #[test]
+    fn vector_versions() {
+        let mut vector = PersistentVector::new();
+        let mut snapshots = Vec::new();
+
+        for chunk in 0..4 {
+            snapshots.push(vector.clone());
+
+            for i in 500 * chunk..500 * (chunk + 1) {
+                vector = vector.push(i);
+            }
+        }
+
+        assert_eq!(2000, vector.len());
+        assert!(vector.iter().copied().eq(0..2000));
+
+        for (n, snapshot) in snapshots.iter().enumerate() {
+            assert!(snapshot.iter().copied().eq(0..500 * n));
+        }
+
+        let changed = vector.set(1234, 0);
+        assert_eq!(Some(&1234), vector.get(1234));
+        assert_eq!(Some(&0), changed.get(1234));
+        assert_eq!(None, changed.get(2000));
+
+        let mut popped = changed.clone();
+        for _ in 0..1990 {
+            popped = popped.pop().unwrap();
+        }
+
+        assert!(popped.iter().copied().eq(0..10));
+        assert_eq!(2000, changed.len());
+        assert!(PersistentVector::<i32>::new().pop().is_none());
+    }
+}
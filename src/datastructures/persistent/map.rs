@@ -0,0 +1,269 @@
+//! Persistent ordered map: an AVL tree updated by path copying.
+//!
+//! An update rebuilds the nodes from the root down to the changed node, rebalancing them on the way back up, and
+//! shares every other subtree with the previous version. The keys and values on the path are cloned.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+
+type Link<K, V> = Option<Arc<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    height: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+fn height<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn node<K, V>(key: K, value: V, left: Link<K, V>, right: Link<K, V>) -> Arc<Node<K, V>> {
+    Arc::new(Node {
+        height: 1 + height(&left).max(height(&right)),
+        key,
+        value,
+        left,
+        right,
+    })
+}
+
+/// Builds a node from balanced subtrees whose heights differ by at most 2, rotating if needed.
+fn balance<K: Clone, V: Clone>(
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+) -> Arc<Node<K, V>> {
+    let (hl, hr) = (height(&left), height(&right));
+
+    if hl > hr + 1 {
+        let l = left.unwrap();
+
+        if height(&l.left) >= height(&l.right) {
+            let right = node(key, value, l.right.clone(), right);
+            node(l.key.clone(), l.value.clone(), l.left.clone(), Some(right))
+        } else {
+            let lr = l.right.as_ref().unwrap();
+            let left = node(
+                l.key.clone(),
+                l.value.clone(),
+                l.left.clone(),
+                lr.left.clone(),
+            );
+            let right = node(key, value, lr.right.clone(), right);
+            node(lr.key.clone(), lr.value.clone(), Some(left), Some(right))
+        }
+    } else if hr > hl + 1 {
+        let r = right.unwrap();
+
+        if height(&r.right) >= height(&r.left) {
+            let left = node(key, value, left, r.left.clone());
+            node(r.key.clone(), r.value.clone(), Some(left), r.right.clone())
+        } else {
+            let rl = r.left.as_ref().unwrap();
+            let left = node(key, value, left, rl.left.clone());
+            let right = node(
+                r.key.clone(),
+                r.value.clone(),
+                rl.right.clone(),
+                r.right.clone(),
+            );
+            node(rl.key.clone(), rl.value.clone(), Some(left), Some(right))
+        }
+    } else {
+        node(key, value, left, right)
+    }
+}
+
+/// Returns the new subtree and whether the key is new.
+fn insert<K: Ord + Clone, V: Clone>(
+    link: &Link<K, V>,
+    key: K,
+    value: V,
+) -> (Arc<Node<K, V>>, bool) {
+    let n = match link {
+        None => return (node(key, value, None, None), true),
+        Some(n) => n,
+    };
+
+    match key.cmp(&n.key) {
+        Ordering::Less => {
+            let (left, added) = insert(&n.left, key, value);
+            let root = balance(n.key.clone(), n.value.clone(), Some(left), n.right.clone());
+            (root, added)
+        }
+        Ordering::Greater => {
+            let (right, added) = insert(&n.right, key, value);
+            let root = balance(n.key.clone(), n.value.clone(), n.left.clone(), Some(right));
+            (root, added)
+        }
+        Ordering::Equal => (node(key, value, n.left.clone(), n.right.clone()), false),
+    }
+}
+
+/// Returns the new subtree, or `None` if the key is not in the tree.
+fn remove<K: Ord + Clone, V: Clone>(link: &Link<K, V>, key: &K) -> Option<Link<K, V>> {
+    let n = link.as_ref()?;
+
+    let root = match key.cmp(&n.key) {
+        Ordering::Less => {
+            let left = remove(&n.left, key)?;
+            balance(n.key.clone(), n.value.clone(), left, n.right.clone())
+        }
+        Ordering::Greater => {
+            let right = remove(&n.right, key)?;
+            balance(n.key.clone(), n.value.clone(), n.left.clone(), right)
+        }
+        Ordering::Equal => match (&n.left, &n.right) {
+            (None, child) | (child, None) => return Some(child.clone()),
+            (left, Some(right)) => {
+                // the successor takes the place of the removed node
+                let (key, value, right) = remove_min(right);
+                balance(key, value, left.clone(), right)
+            }
+        },
+    };
+
+    Some(Some(root))
+}
+
+fn remove_min<K: Clone, V: Clone>(n: &Arc<Node<K, V>>) -> (K, V, Link<K, V>) {
+    match &n.left {
+        None => (n.key.clone(), n.value.clone(), n.right.clone()),
+        Some(left) => {
+            let (key, value, left) = remove_min(left);
+            let root = balance(n.key.clone(), n.value.clone(), left, n.right.clone());
+            (key, value, Some(root))
+        }
+    }
+}
+
+pub struct PersistentMap<K, V> {
+    root: Link<K, V>,
+    len: usize,
+}
+
+impl<K: Ord + Clone, V: Clone> PersistentMap<K, V> {
+    pub fn new() -> Self {
+        Self { root: None, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn height(&self) -> usize {
+        height(&self.root)
+    }
+
+    /// Returns a new version with `value` under `key`.
+    pub fn insert(&self, key: K, value: V) -> Self {
+        let (root, added) = insert(&self.root, key, value);
+
+        Self {
+            root: Some(root),
+            len: self.len + added as usize,
+        }
+    }
+
+    /// Returns a new version without `key`. It shares the whole tree if the key is not present.
+    pub fn remove(&self, key: &K) -> Self {
+        match remove(&self.root, key) {
+            Some(root) => Self {
+                root,
+                len: self.len - 1,
+            },
+            None => self.clone(),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut link = &self.root;
+
+        while let Some(n) = link {
+            link = match key.cmp(&n.key) {
+                Ordering::Less => &n.left,
+                Ordering::Greater => &n.right,
+                Ordering::Equal => return Some(&n.value),
+            };
+        }
+
+        None
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Iterates over the entries in key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            len: self.len,
+        };
+        iter.push_left(&self.root);
+        iter
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Default for PersistentMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Clone for PersistentMap<K, V> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+        }
+    }
+}
+
+impl<K: Ord + Clone + fmt::Debug, V: Clone + fmt::Debug> fmt::Debug for PersistentMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// In-order iterator over the entries of a `PersistentMap`.
+pub struct Iter<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    len: usize,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(n) = link {
+            self.stack.push(n);
+            link = &n.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.stack.pop()?;
+        self.push_left(&n.right);
+        self.len -= 1;
+
+        Some((&n.key, &n.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<K, V> ExactSizeIterator for Iter<'_, K, V> {}
@@ -0,0 +1,271 @@
+//! Persistent vector: an array mapped trie.
+//!
+//! The elements are stored in leaves of 32 elements, under branch nodes of 32 children. The index of an element
+//! is read 5 bits at a time from the most significant end to find the child at every level, so the trie of
+//! `n` elements has `log32 n` levels, which is at most 7 for 32 bit indices. An update copies the nodes on the path
+//! to the element and shares all the others.
+
+use std::fmt;
+use std::sync::Arc;
+
+const BITS: usize = 5;
+const WIDTH: usize = 1 << BITS;
+const MASK: usize = WIDTH - 1;
+
+enum Node<T> {
+    Branch(Vec<Arc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+/// A path of nodes down to a leaf holding only `value`, with the leaf `level` bits below.
+fn new_path<T>(level: usize, value: T) -> Arc<Node<T>> {
+    if level == 0 {
+        Arc::new(Node::Leaf(vec![value]))
+    } else {
+        Arc::new(Node::Branch(vec![new_path(level - BITS, value)]))
+    }
+}
+
+/// Appends `value` at `idx`, the first index after the end, to a node that has room for it.
+fn push<T: Clone>(node: &Node<T>, level: usize, idx: usize, value: T) -> Arc<Node<T>> {
+    match node {
+        Node::Leaf(values) => {
+            let mut values = values.clone();
+            values.push(value);
+            Arc::new(Node::Leaf(values))
+        }
+        Node::Branch(children) => {
+            let slot = (idx >> level) & MASK;
+            let mut children = children.clone();
+
+            if slot < children.len() {
+                children[slot] = push(&children[slot], level - BITS, idx, value);
+            } else {
+                children.push(new_path(level - BITS, value));
+            }
+
+            Arc::new(Node::Branch(children))
+        }
+    }
+}
+
+/// Removes the element at `idx`, the last index. Returns `None` if the node becomes empty.
+fn pop<T: Clone>(node: &Node<T>, level: usize, idx: usize) -> Option<Arc<Node<T>>> {
+    match node {
+        Node::Leaf(values) => {
+            if values.len() == 1 {
+                None
+            } else {
+                Some(Arc::new(Node::Leaf(values[..values.len() - 1].to_vec())))
+            }
+        }
+        Node::Branch(children) => {
+            let slot = (idx >> level) & MASK;
+            let mut children = children.clone();
+
+            match pop(&children[slot], level - BITS, idx) {
+                Some(child) => children[slot] = child,
+                None => {
+                    children.pop();
+                }
+            }
+
+            if children.is_empty() {
+                None
+            } else {
+                Some(Arc::new(Node::Branch(children)))
+            }
+        }
+    }
+}
+
+fn set<T: Clone>(node: &Node<T>, level: usize, idx: usize, value: T) -> Arc<Node<T>> {
+    match node {
+        Node::Leaf(values) => {
+            let mut values = values.clone();
+            values[idx & MASK] = value;
+            Arc::new(Node::Leaf(values))
+        }
+        Node::Branch(children) => {
+            let slot = (idx >> level) & MASK;
+            let mut children = children.clone();
+            children[slot] = set(&children[slot], level - BITS, idx, value);
+            Arc::new(Node::Branch(children))
+        }
+    }
+}
+
+pub struct PersistentVector<T> {
+    root: Arc<Node<T>>,
+    len: usize,
+    /// Number of index bits below the root, i.e. `BITS` times the number of branch levels.
+    shift: usize,
+}
+
+impl<T: Clone> PersistentVector<T> {
+    pub fn new() -> Self {
+        Self {
+            root: Arc::new(Node::Leaf(Vec::new())),
+            len: 0,
+            shift: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        if idx >= self.len {
+            return None;
+        }
+
+        let mut node = &*self.root;
+        let mut level = self.shift;
+
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = &children[(idx >> level) & MASK];
+                    level -= BITS;
+                }
+                Node::Leaf(values) => return values.get(idx & MASK),
+            }
+        }
+    }
+
+    /// Returns a new version with the element at `idx` replaced by `value`.
+    ///
+    /// Panics if `idx` is out of bounds.
+    pub fn set(&self, idx: usize, value: T) -> Self {
+        assert!(idx < self.len, "index out of bounds");
+
+        Self {
+            root: set(&self.root, self.shift, idx, value),
+            len: self.len,
+            shift: self.shift,
+        }
+    }
+
+    /// Returns a new version with `value` appended.
+    pub fn push(&self, value: T) -> Self {
+        let capacity = 1 << (self.shift + BITS);
+
+        if self.len == capacity {
+            // the trie is full, add a level on top
+            let root = Node::Branch(vec![self.root.clone(), new_path(self.shift, value)]);
+
+            Self {
+                root: Arc::new(root),
+                len: self.len + 1,
+                shift: self.shift + BITS,
+            }
+        } else {
+            Self {
+                root: push(&self.root, self.shift, self.len, value),
+                len: self.len + 1,
+                shift: self.shift,
+            }
+        }
+    }
+
+    /// Returns the version without the last element, or `None` if the vector is empty.
+    pub fn pop(&self) -> Option<Self> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let mut root = match pop(&self.root, self.shift, self.len - 1) {
+            Some(root) => root,
+            None => return Some(Self::new()),
+        };
+        let mut shift = self.shift;
+
+        // drop the levels left with a single child
+        while let Node::Branch(children) = &*root {
+            if children.len() > 1 {
+                break;
+            }
+
+            let child = children[0].clone();
+            root = child;
+            shift -= BITS;
+        }
+
+        Some(Self {
+            root,
+            len: self.len - 1,
+            shift,
+        })
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            vector: self,
+            front: 0,
+            back: self.len,
+        }
+    }
+}
+
+impl<T: Clone> Default for PersistentVector<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for PersistentVector<T> {
+    fn clone(&self) -> Self {
+        Self {
+            root: self.root.clone(),
+            len: self.len,
+            shift: self.shift,
+        }
+    }
+}
+
+impl<T: Clone + fmt::Debug> fmt::Debug for PersistentVector<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a, T> {
+    vector: &'a PersistentVector<T>,
+    front: usize,
+    back: usize,
+}
+
+impl<'a, T: Clone> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.front += 1;
+        self.vector.get(self.front - 1)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.back - self.front, Some(self.back - self.front))
+    }
+}
+
+impl<T: Clone> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+
+        self.back -= 1;
+        self.vector.get(self.back)
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for Iter<'_, T> {}
@@ -0,0 +1,179 @@
+//! FIFO queue built on top of the ring-buffer `Deque`.
+//!
+//! Elements are enqueued at the back and dequeued from the front. `dequeue` and `peek` are O(1),
+//! `enqueue` is O(1) amortized: see the analysis in the `deque` module, the buffer doubles when full.
+
+use std::iter::FromIterator;
+
+use super::deque::{self, Deque};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Queue<T> {
+    deque: Deque<T>,
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Queue<T> {
+    pub fn new() -> Self {
+        Self {
+            deque: Deque::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            deque: Deque::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.deque.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.deque.is_empty()
+    }
+
+    /// Adds `value` at the back of the queue in O(1) amortized.
+    pub fn enqueue(&mut self, value: T) {
+        self.deque.push_back(value);
+    }
+
+    /// Removes the front of the queue in O(1).
+    pub fn dequeue(&mut self) -> Option<T> {
+        self.deque.pop_front()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.deque.front()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.deque.front_mut()
+    }
+
+    /// Iterates from the front to the back of the queue.
+    pub fn iter(&self) -> deque::Iter<'_, T> {
+        self.deque.iter()
+    }
+}
+
+impl<T> FromIterator<T> for Queue<T> {
+    fn from_iter<It: IntoIterator<Item = T>>(iter: It) -> Self {
+        Self {
+            deque: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<T> Extend<T> for Queue<T> {
+    fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
+        self.deque.extend(iter);
+    }
+}
+
+impl<T> IntoIterator for Queue<T> {
+    type Item = T;
+    type IntoIter = deque::IntoIter<T>;
+
+    /// Yields the elements in the order they would be dequeued.
+    fn into_iter(self) -> Self::IntoIter {
+        self.deque.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Queue<T> {
+    type Item = &'a T;
+    type IntoIter = deque::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+
+    use super::Queue;
+    use crate::random::Rng;
+
+    #[test]
+    fn fifo() {
+        let mut queue: Queue<_> = (0..3).collect();
+
+        queue.enqueue(3);
+
+        assert_eq!(Some(&0), queue.peek());
+        assert_eq!(Some(0), queue.dequeue());
+        assert_eq!(vec![1, 2, 3], queue.iter().copied().collect::<Vec<_>>());
+
+        // interleave to force the ring buffer to wrap
+        for i in 4..20 {
+            queue.enqueue(i);
+            queue.dequeue();
+        }
+
+        assert_eq!(vec![17, 18, 19], queue.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty() {
+        let mut queue = Queue::<i32>::with_capacity(0);
+
+        assert_eq!(None, queue.peek());
+        assert_eq!(None, queue.peek_mut());
+        assert_eq!(None, queue.dequeue());
+        assert!(queue.is_empty());
+
+        queue.enqueue(1);
+        assert_eq!(Some(1), queue.dequeue());
+        assert_eq!(None, queue.dequeue());
+        assert_eq!(0, queue.len());
+    }
+
+    #[test]
+    fn against_vec_deque() {
+        let mut rng = Rng::new(26);
+        let mut queue = Queue::new();
+        let mut expected = VecDeque::new();
+
+        for i in 0..2000 {
+            // more enqueues than dequeues, so that the buffer grows while wrapped
+            if rng.below(5) < 3 {
+                queue.enqueue(i);
+                expected.push_back(i);
+            } else {
+                assert_eq!(expected.pop_front(), queue.dequeue());
+            }
+
+            assert_eq!(expected.front(), queue.peek());
+            assert_eq!(expected.len(), queue.len());
+        }
+
+        assert!(expected.iter().eq(queue.iter()));
+        assert!(expected.into_iter().eq(queue));
+    }
+
+    #[test]
+    fn drops_remaining() {
+        let counter = Rc::new(());
+        let mut queue: Queue<_> = (0..6).map(|_| Rc::clone(&counter)).collect();
+
+        queue.dequeue();
+        queue.enqueue(Rc::clone(&counter));
+
+        let mut iter = queue.into_iter();
+        iter.next();
+        assert_eq!(6, Rc::strong_count(&counter));
+
+        drop(iter);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+}
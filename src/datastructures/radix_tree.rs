@@ -0,0 +1,368 @@
+//! Radix tree (compressed trie) mapping byte strings to values.
+//!
+//! Like a `Trie`, but chains of nodes with a single child and no value are merged into one edge labelled
+//! with a whole byte string. Thus the tree has at most `2n` nodes for `n` keys, no matter how long they are,
+//! which makes it a good fit for routing tables and other sets of long keys with shared prefixes.
+//!
+//! The invariant, restored after every removal, is that every node other than the root either holds a value
+//! or has at least two children.
+
+use std::collections::BTreeMap;
+
+struct Node<V> {
+    /// Bytes on the edge from the parent. Empty only for the root.
+    label: Vec<u8>,
+    value: Option<V>,
+    /// Number of values in this subtree, including the node itself.
+    count: usize,
+    /// Children indexed by the first byte of their label.
+    children: BTreeMap<u8, Node<V>>,
+}
+
+fn common_prefix_len(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+impl<V> Node<V> {
+    fn new(label: Vec<u8>, value: Option<V>) -> Self {
+        Self {
+            label,
+            count: value.is_some() as usize,
+            value,
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Inserts `key`, relative to this node, in the subtree.
+    fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        let old = match key.first() {
+            None => self.value.replace(value),
+            Some(&byte) => match self.children.get_mut(&byte) {
+                None => {
+                    self.children
+                        .insert(byte, Node::new(key.to_vec(), Some(value)));
+                    None
+                }
+                Some(child) => {
+                    let common = common_prefix_len(&child.label, key);
+
+                    if common < child.label.len() {
+                        child.split(common);
+                    }
+
+                    child.insert(&key[common..], value)
+                }
+            },
+        };
+
+        if old.is_none() {
+            self.count += 1;
+        }
+
+        old
+    }
+
+    /// Splits the label after `at` bytes: this node keeps the first part and gets a single child
+    /// with the rest of the label and all the previous content.
+    fn split(&mut self, at: usize) {
+        let suffix = self.label.split_off(at);
+        let mut lower = Node::new(suffix, self.value.take());
+
+        lower.count = self.count;
+        lower.children = std::mem::take(&mut self.children);
+
+        self.children.insert(lower.label[0], lower);
+    }
+
+    /// Removes `key`, relative to this node, from the subtree.
+    fn remove(&mut self, key: &[u8]) -> Option<V> {
+        let removed = match key.first() {
+            None => self.value.take(),
+            Some(byte) => {
+                let child = self.children.get_mut(byte)?;
+
+                if !key.starts_with(&child.label) {
+                    return None;
+                }
+
+                let removed = child.remove(&key[child.label.len()..]);
+
+                if child.count == 0 {
+                    self.children.remove(byte);
+                } else if child.value.is_none() && child.children.len() == 1 {
+                    child.merge_with_child();
+                }
+
+                removed
+            }
+        };
+
+        if removed.is_some() {
+            self.count -= 1;
+        }
+
+        removed
+    }
+
+    /// Absorbs the only child of a node without a value.
+    fn merge_with_child(&mut self) {
+        let (_, child) = std::mem::take(&mut self.children)
+            .into_iter()
+            .next()
+            .expect("node has a child");
+
+        self.label.extend(child.label);
+        self.value = child.value;
+        self.children = child.children;
+    }
+}
+
+pub struct RadixTree<V> {
+    root: Node<V>,
+}
+
+impl<V> Default for RadixTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> RadixTree<V> {
+    pub fn new() -> Self {
+        Self {
+            root: Node::new(vec![], None),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts `value` under `key`, returning the previous value if the key was present.
+    pub fn insert(&mut self, key: impl AsRef<[u8]>, value: V) -> Option<V> {
+        self.root.insert(key.as_ref(), value)
+    }
+
+    /// Removes `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) -> Option<V> {
+        self.root.remove(key.as_ref())
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<&V> {
+        let mut node = &self.root;
+        let mut key = key.as_ref();
+
+        while let Some(byte) = key.first() {
+            node = node.children.get(byte)?;
+            key = key.strip_prefix(node.label.as_slice())?;
+        }
+
+        node.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, key: impl AsRef<[u8]>) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        let mut key = key.as_ref();
+
+        while let Some(byte) = key.first() {
+            node = node.children.get_mut(byte)?;
+            key = key.strip_prefix(node.label.as_slice())?;
+        }
+
+        node.value.as_mut()
+    }
+
+    pub fn contains_key(&self, key: impl AsRef<[u8]>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Number of keys starting with `prefix`.
+    pub fn count_prefix(&self, prefix: impl AsRef<[u8]>) -> usize {
+        self.find_prefix(prefix.as_ref())
+            .map_or(0, |(_, node)| node.count)
+    }
+
+    /// The longest key which is a prefix of `key`, together with its value.
+    pub fn longest_prefix<'k>(&self, key: &'k [u8]) -> Option<(&'k [u8], &V)> {
+        let mut node = &self.root;
+        let mut matched = 0;
+        let mut longest = node.value.as_ref().map(|value| (&key[..0], value));
+
+        while let Some(byte) = key.get(matched) {
+            node = match node.children.get(byte) {
+                Some(child) if key[matched..].starts_with(&child.label) => child,
+                _ => break,
+            };
+
+            matched += node.label.len();
+
+            if let Some(value) = &node.value {
+                longest = Some((&key[..matched], value));
+            }
+        }
+
+        longest
+    }
+
+    /// Iterates over the keys starting with `prefix` and their values, in lexicographic order.
+    pub fn iter_prefix(&self, prefix: impl AsRef<[u8]>) -> Iter<'_, V> {
+        let stack = self
+            .find_prefix(prefix.as_ref())
+            .map(|start| vec![start])
+            .unwrap_or_default();
+
+        Iter { stack }
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix([])
+    }
+
+    /// Finds the highest node whose key starts with `prefix`, and returns it with its key.
+    fn find_prefix(&self, prefix: &[u8]) -> Option<(Vec<u8>, &Node<V>)> {
+        let mut node = &self.root;
+        let mut matched = 0;
+
+        while let Some(byte) = prefix.get(matched) {
+            node = node.children.get(byte)?;
+
+            let rest = &prefix[matched..];
+
+            if node.label.starts_with(rest) {
+                // the prefix ends in the middle of (or exactly at the end of) this edge
+                let mut key = prefix[..matched].to_vec();
+                key.extend_from_slice(&node.label);
+
+                return Some((key, node));
+            } else if rest.starts_with(&node.label) {
+                matched += node.label.len();
+            } else {
+                return None;
+            }
+        }
+
+        Some((prefix.to_vec(), node))
+    }
+}
+
+/// Pre-order depth-first iterator: a key is always yielded before its extensions.
+pub struct Iter<'a, V> {
+    stack: Vec<(Vec<u8>, &'a Node<V>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, node)) = self.stack.pop() {
+            // push in reverse so that the smallest label is visited first
+            for child in node.children.values().rev() {
+                let mut child_key = key.clone();
+                child_key.extend_from_slice(&child.label);
+                self.stack.push((child_key, child));
+            }
+
+            if let Some(value) = &node.value {
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RadixTree;
+
+    fn keys<V>(iter: impl Iterator<Item = (Vec<u8>, V)>) -> Vec<String> {
+        iter.map(|(key, _)| String::from_utf8(key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut tree = RadixTree::new();
+
+        assert_eq!(None, tree.insert("romane", 1));
+        assert_eq!(None, tree.insert("romanus", 2));
+        assert_eq!(None, tree.insert("romulus", 3));
+        assert_eq!(None, tree.insert("rom", 4));
+        assert_eq!(Some(4), tree.insert("rom", 5));
+
+        assert_eq!(4, tree.len());
+        assert_eq!(Some(&1), tree.get("romane"));
+        assert_eq!(Some(&5), tree.get("rom"));
+        assert_eq!(None, tree.get("roman"));
+        assert_eq!(None, tree.get("romanes"));
+
+        *tree.get_mut("romulus").unwrap() *= 10;
+        assert_eq!(Some(&30), tree.get("romulus"));
+
+        assert_eq!(None, tree.remove("roman"));
+        assert_eq!(Some(1), tree.remove("romane"));
+        assert_eq!(Some(5), tree.remove("rom"));
+
+        // "an" and "us" have been merged into a single edge below "rom"
+        assert_eq!(Some(&2), tree.get("romanus"));
+        assert_eq!(Some(&30), tree.get("romulus"));
+        assert_eq!(2, tree.len());
+        assert_eq!(1, tree.root.children.len());
+
+        assert_eq!(Some(2), tree.remove("romanus"));
+        assert_eq!(Some(30), tree.remove("romulus"));
+        assert!(tree.is_empty());
+        assert!(tree.root.children.is_empty());
+    }
+
+    #[test]
+    fn prefix_queries() {
+        let mut tree = RadixTree::new();
+
+        for (idx, route) in [
+            "/",
+            "/api",
+            "/api/users",
+            "/api/users/me",
+            "/assets",
+            "/index",
+        ]
+        .iter()
+        .enumerate()
+        {
+            tree.insert(route, idx);
+        }
+
+        assert_eq!(6, tree.count_prefix(""));
+        assert_eq!(4, tree.count_prefix("/a"));
+        assert_eq!(2, tree.count_prefix("/api/u"));
+        assert_eq!(0, tree.count_prefix("/apx"));
+
+        assert_eq!(
+            vec!["/api/users", "/api/users/me"],
+            keys(tree.iter_prefix("/api/us"))
+        );
+        assert_eq!(
+            vec![
+                "/",
+                "/api",
+                "/api/users",
+                "/api/users/me",
+                "/assets",
+                "/index"
+            ],
+            keys(tree.iter())
+        );
+
+        assert_eq!(
+            Some((&b"/api/users"[..], &2)),
+            tree.longest_prefix(b"/api/users/42")
+        );
+        assert_eq!(Some((&b"/"[..], &0)), tree.longest_prefix(b"/about"));
+        assert_eq!(None, tree.longest_prefix(b"about"));
+    }
+}
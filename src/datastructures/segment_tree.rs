@@ -0,0 +1,711 @@
+//! Segment trees over an arbitrary monoid.
+//!
+//! A segment tree stores the elements in the leaves of a complete binary tree and, in every inner node,
+//! the combination of the two children. Any range `l..r` is covered by O(log n) nodes, hence range queries
+//! and point updates take O(log n) time.
+//!
+//! The tree is stored in an array like `datastructures::heap`: the root is at index 1 and the children of `i`
+//! are at `2i` and `2i + 1`. The number of leaves is rounded up to a power of two, the padding leaves hold the
+//! identity of the monoid.
+//!
+//! `LazySegmentTree` additionally supports updating a whole range in O(log n): an update covering a node is
+//! applied to the node and kept as a pending tag, which is pushed to the children only when they're visited.
+
+use std::convert::TryFrom;
+use std::marker::PhantomData;
+use std::ops::{Add, Bound, Mul, RangeBounds, Rem};
+
+/// An associative operation with an identity element.
+///
+/// `combine(identity(), a) == a == combine(a, identity())` and
+/// `combine(combine(a, b), c) == combine(a, combine(b, c))` must hold.
+pub trait Monoid {
+    type Value: Clone;
+
+    fn identity() -> Self::Value;
+    fn combine(a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// A monoid whose aggregated values can be updated lazily.
+///
+/// `apply` must distribute over `combine`: applying an update to the combination of `len` elements
+/// must be equal to combining the updated elements.
+pub trait LazyMonoid: Monoid {
+    type Update: Clone;
+
+    /// Applies `update` to `value`, the combination of `len` consecutive elements.
+    fn apply(update: &Self::Update, value: &Self::Value, len: usize) -> Self::Value;
+    /// Returns the update equivalent to applying `older` and then `newer`.
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update;
+}
+
+/// Types with a smallest and a greatest value, used as the identities of `Max` and `Min`.
+pub trait Bounded {
+    const MIN: Self;
+    const MAX: Self;
+}
+
+macro_rules! impl_bounded {
+    ($($t:ty),*) => {
+        $(impl Bounded for $t {
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+        })*
+    };
+}
+
+impl_bounded!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+pub struct Sum<T>(PhantomData<T>);
+pub struct Min<T>(PhantomData<T>);
+pub struct Max<T>(PhantomData<T>);
+/// Greatest common divisor, meant for non-negative values.
+pub struct Gcd<T>(PhantomData<T>);
+
+impl<T: Copy + Default + Add<Output = T>> Monoid for Sum<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a + *b
+    }
+}
+
+impl<T: Copy + Ord + Bounded> Monoid for Min<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::MAX
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a.min(b)
+    }
+}
+
+impl<T: Copy + Ord + Bounded> Monoid for Max<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::MIN
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        *a.max(b)
+    }
+}
+
+impl<T: Copy + Default + PartialEq + Rem<Output = T>> Monoid for Gcd<T> {
+    type Value = T;
+
+    fn identity() -> T {
+        T::default()
+    }
+
+    fn combine(a: &T, b: &T) -> T {
+        let (mut a, mut b) = (*a, *b);
+
+        while b != T::default() {
+            let r = a % b;
+            a = b;
+            b = r;
+        }
+
+        a
+    }
+}
+
+/// Range update supported by `Sum`, `Min` and `Max` in a `LazySegmentTree`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeUpdate<T> {
+    /// Add the value to every element of the range.
+    Add(T),
+    /// Replace every element of the range with the value.
+    Assign(T),
+}
+
+impl<T: Copy + Add<Output = T>> RangeUpdate<T> {
+    fn compose(newer: &Self, older: &Self) -> Self {
+        match (*newer, *older) {
+            (RangeUpdate::Add(a), RangeUpdate::Add(b)) => RangeUpdate::Add(a + b),
+            (RangeUpdate::Add(a), RangeUpdate::Assign(x)) => RangeUpdate::Assign(x + a),
+            (RangeUpdate::Assign(x), _) => RangeUpdate::Assign(x),
+        }
+    }
+}
+
+impl<T> LazyMonoid for Sum<T>
+where
+    T: Copy + Default + Add<Output = T> + Mul<Output = T> + TryFrom<usize>,
+{
+    type Update = RangeUpdate<T>;
+
+    fn apply(update: &Self::Update, value: &T, len: usize) -> T {
+        let len = T::try_from(len).ok().expect("range length must fit in T");
+
+        match *update {
+            RangeUpdate::Add(a) => *value + a * len,
+            RangeUpdate::Assign(x) => x * len,
+        }
+    }
+
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update {
+        RangeUpdate::compose(newer, older)
+    }
+}
+
+impl<T: Copy + Ord + Bounded + Add<Output = T>> LazyMonoid for Min<T> {
+    type Update = RangeUpdate<T>;
+
+    fn apply(update: &Self::Update, value: &T, _len: usize) -> T {
+        match *update {
+            RangeUpdate::Add(a) => *value + a,
+            RangeUpdate::Assign(x) => x,
+        }
+    }
+
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update {
+        RangeUpdate::compose(newer, older)
+    }
+}
+
+impl<T: Copy + Ord + Bounded + Add<Output = T>> LazyMonoid for Max<T> {
+    type Update = RangeUpdate<T>;
+
+    fn apply(update: &Self::Update, value: &T, _len: usize) -> T {
+        match *update {
+            RangeUpdate::Add(a) => *value + a,
+            RangeUpdate::Assign(x) => x,
+        }
+    }
+
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update {
+        RangeUpdate::compose(newer, older)
+    }
+}
+
+/// Converts `range` to a half-open `(start, end)` pair, panicking if it's out of `0..len`.
+pub(crate) fn to_bounds(range: impl RangeBounds<usize>, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s + 1,
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e + 1,
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => len,
+    };
+
+    assert!(
+        start <= end && end <= len,
+        "range {}..{} out of bounds for length {}",
+        start,
+        end,
+        len
+    );
+
+    (start, end)
+}
+
+/// Segment tree supporting point updates and range queries in O(log n).
+pub struct SegmentTree<M: Monoid> {
+    len: usize,
+    /// Number of leaves, a power of two. Leaf `i` is at `tree[size + i]`.
+    size: usize,
+    tree: Vec<M::Value>,
+}
+
+impl<M: Monoid> SegmentTree<M> {
+    /// Builds the tree in O(n).
+    pub fn new(values: Vec<M::Value>) -> Self {
+        let len = values.len();
+        let size = len.next_power_of_two();
+        let mut tree = vec![M::identity(); size];
+
+        tree.extend(values);
+        tree.resize(2 * size, M::identity());
+
+        for node in (1..size).rev() {
+            tree[node] = M::combine(&tree[2 * node], &tree[2 * node + 1]);
+        }
+
+        Self { len, size, tree }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, idx: usize) -> &M::Value {
+        assert!(idx < self.len, "index {} out of bounds", idx);
+
+        &self.tree[self.size + idx]
+    }
+
+    /// Replaces the element at `idx` and fixes its ancestors in O(log n).
+    pub fn set(&mut self, idx: usize, value: M::Value) {
+        assert!(idx < self.len, "index {} out of bounds", idx);
+
+        let mut node = self.size + idx;
+        self.tree[node] = value;
+
+        while node > 1 {
+            node /= 2;
+            self.tree[node] = M::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+        }
+    }
+
+    /// Combines the elements in `range` in O(log n). Empty ranges return the identity.
+    pub fn query(&self, range: impl RangeBounds<usize>) -> M::Value {
+        let (start, end) = to_bounds(range, self.len);
+        let (mut l, mut r) = (start + self.size, end + self.size);
+        let (mut left, mut right) = (M::identity(), M::identity());
+
+        // Climb from both ends, the order of the operands is kept since the monoid may not be commutative.
+        while l < r {
+            if l % 2 == 1 {
+                left = M::combine(&left, &self.tree[l]);
+                l += 1;
+            }
+
+            if r % 2 == 1 {
+                r -= 1;
+                right = M::combine(&self.tree[r], &right);
+            }
+
+            l /= 2;
+            r /= 2;
+        }
+
+        M::combine(&left, &right)
+    }
+
+    /// Returns the smallest `end >= from` such that `pred(query(from..=end))` holds, in O(log n).
+    ///
+    /// `pred` must be monotone: once it holds for a range, it must hold for every longer one
+    /// (e.g. "the sum is at least `k`" for non-negative values, or "the max is at least `k`").
+    pub fn find_first(&self, from: usize, pred: impl Fn(&M::Value) -> bool) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+
+        let mut node = from + self.size;
+        let mut acc = M::identity();
+
+        loop {
+            // go up while `node` is a left child: its parent covers the same start
+            node >>= node.trailing_zeros();
+
+            let candidate = M::combine(&acc, &self.tree[node]);
+
+            if pred(&candidate) {
+                // the answer is in this subtree: go down, preferring the left child
+                while node < self.size {
+                    node *= 2;
+                    let candidate = M::combine(&acc, &self.tree[node]);
+
+                    if !pred(&candidate) {
+                        acc = candidate;
+                        node += 1;
+                    }
+                }
+
+                let idx = node - self.size;
+                return if idx < self.len { Some(idx) } else { None };
+            }
+
+            acc = candidate;
+            node += 1;
+
+            // `node` is a power of two only after the rightmost node of a level was consumed
+            if node.is_power_of_two() {
+                return None;
+            }
+        }
+    }
+}
+
+/// Segment tree that also supports range updates in O(log n).
+pub struct LazySegmentTree<M: LazyMonoid> {
+    len: usize,
+    size: usize,
+    tree: Vec<M::Value>,
+    /// Pending update of each inner node, already applied to the node but not to its children.
+    lazy: Vec<Option<M::Update>>,
+}
+
+impl<M: LazyMonoid> LazySegmentTree<M> {
+    /// Builds the tree in O(n).
+    pub fn new(values: Vec<M::Value>) -> Self {
+        let SegmentTree { len, size, tree } = SegmentTree::<M>::new(values);
+
+        Self {
+            len,
+            size,
+            tree,
+            lazy: vec![None; size],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, idx: usize) -> M::Value {
+        assert!(idx < self.len, "index {} out of bounds", idx);
+
+        self.query(idx..=idx)
+    }
+
+    /// Replaces the element at `idx` in O(log n).
+    pub fn set(&mut self, idx: usize, value: M::Value) {
+        assert!(idx < self.len, "index {} out of bounds", idx);
+
+        self.set_rec(1, 0, self.size, idx, value);
+    }
+
+    /// Applies `update` to every element in `range` in O(log n).
+    pub fn update(&mut self, range: impl RangeBounds<usize>, update: M::Update) {
+        let (start, end) = to_bounds(range, self.len);
+
+        // Padding leaves are never inside `start..end`, so they keep the identity.
+        self.update_rec(1, 0, self.size, start, end, &update);
+    }
+
+    /// Combines the elements in `range` in O(log n). Empty ranges return the identity.
+    pub fn query(&self, range: impl RangeBounds<usize>) -> M::Value {
+        let (start, end) = to_bounds(range, self.len);
+
+        self.query_rec(1, 0, self.size, start, end, None)
+    }
+
+    /// Same as `SegmentTree::find_first`.
+    pub fn find_first(&self, from: usize, pred: impl Fn(&M::Value) -> bool) -> Option<usize> {
+        if from >= self.len {
+            return None;
+        }
+
+        let mut acc = M::identity();
+
+        self.find_first_rec(1, 0, self.size, from, &mut acc, &pred, None)
+            .filter(|&idx| idx < self.len)
+    }
+
+    fn apply_node(&mut self, node: usize, update: &M::Update, len: usize) {
+        self.tree[node] = M::apply(update, &self.tree[node], len);
+
+        if node < self.size {
+            self.lazy[node] = Some(compose_pending::<M>(Some(update), self.lazy[node].as_ref()));
+        }
+    }
+
+    fn push(&mut self, node: usize, len: usize) {
+        if let Some(update) = self.lazy[node].take() {
+            self.apply_node(2 * node, &update, len / 2);
+            self.apply_node(2 * node + 1, &update, len / 2);
+        }
+    }
+
+    fn pull(&mut self, node: usize) {
+        self.tree[node] = M::combine(&self.tree[2 * node], &self.tree[2 * node + 1]);
+    }
+
+    fn set_rec(&mut self, node: usize, nl: usize, nr: usize, idx: usize, value: M::Value) {
+        if nr - nl == 1 {
+            self.tree[node] = value;
+            return;
+        }
+
+        self.push(node, nr - nl);
+
+        let mid = (nl + nr) / 2;
+
+        if idx < mid {
+            self.set_rec(2 * node, nl, mid, idx, value);
+        } else {
+            self.set_rec(2 * node + 1, mid, nr, idx, value);
+        }
+
+        self.pull(node);
+    }
+
+    fn update_rec(
+        &mut self,
+        node: usize,
+        nl: usize,
+        nr: usize,
+        l: usize,
+        r: usize,
+        update: &M::Update,
+    ) {
+        if r <= nl || nr <= l {
+            return;
+        }
+
+        if l <= nl && nr <= r {
+            self.apply_node(node, update, nr - nl);
+            return;
+        }
+
+        self.push(node, nr - nl);
+
+        let mid = (nl + nr) / 2;
+        self.update_rec(2 * node, nl, mid, l, r, update);
+        self.update_rec(2 * node + 1, mid, nr, l, r, update);
+
+        self.pull(node);
+    }
+
+    /// Queries without pushing: `pending` is the composition of the tags of the ancestors,
+    /// which still has to be applied to `node`.
+    fn query_rec(
+        &self,
+        node: usize,
+        nl: usize,
+        nr: usize,
+        l: usize,
+        r: usize,
+        pending: Option<&M::Update>,
+    ) -> M::Value {
+        if r <= nl || nr <= l {
+            return M::identity();
+        }
+
+        if l <= nl && nr <= r {
+            return self.node_value(node, nr - nl, pending);
+        }
+
+        let pending = self.child_pending(node, pending);
+        let mid = (nl + nr) / 2;
+
+        M::combine(
+            &self.query_rec(2 * node, nl, mid, l, r, pending.as_ref()),
+            &self.query_rec(2 * node + 1, mid, nr, l, r, pending.as_ref()),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn find_first_rec(
+        &self,
+        node: usize,
+        nl: usize,
+        nr: usize,
+        from: usize,
+        acc: &mut M::Value,
+        pred: &impl Fn(&M::Value) -> bool,
+        pending: Option<&M::Update>,
+    ) -> Option<usize> {
+        if nr <= from {
+            return None;
+        }
+
+        if from <= nl {
+            let candidate = M::combine(acc, &self.node_value(node, nr - nl, pending));
+
+            if !pred(&candidate) {
+                *acc = candidate;
+                return None;
+            }
+
+            if nr - nl == 1 {
+                return Some(nl);
+            }
+        }
+
+        let pending = self.child_pending(node, pending);
+        let mid = (nl + nr) / 2;
+
+        self.find_first_rec(2 * node, nl, mid, from, acc, pred, pending.as_ref())
+            .or_else(|| {
+                self.find_first_rec(2 * node + 1, mid, nr, from, acc, pred, pending.as_ref())
+            })
+    }
+
+    fn node_value(&self, node: usize, len: usize, pending: Option<&M::Update>) -> M::Value {
+        match pending {
+            Some(update) => M::apply(update, &self.tree[node], len),
+            None => self.tree[node].clone(),
+        }
+    }
+
+    fn child_pending(&self, node: usize, pending: Option<&M::Update>) -> Option<M::Update> {
+        match (pending, self.lazy[node].as_ref()) {
+            (None, None) => None,
+            (newer, older) => Some(compose_pending::<M>(newer, older)),
+        }
+    }
+}
+
+/// Composes two optional updates, at least one of which must be `Some`.
+fn compose_pending<M: LazyMonoid>(
+    newer: Option<&M::Update>,
+    older: Option<&M::Update>,
+) -> M::Update {
+    match (newer, older) {
+        (Some(newer), Some(older)) => M::compose(newer, older),
+        (Some(update), None) | (None, Some(update)) => update.clone(),
+        (None, None) => unreachable!("no update to compose"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Gcd, LazySegmentTree, Max, Min, Monoid, RangeUpdate, SegmentTree, Sum};
+    use crate::random::Rng;
+
+    /// Non-commutative monoid: string concatenation.
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn combine(a: &String, b: &String) -> String {
+            format!("{}{}", a, b)
+        }
+    }
+
+    #[test]
+    fn point_update_range_query() {
+        let values = vec![5, 3, 8, 6, 1, 4, 7];
+        let mut sum = SegmentTree::<Sum<i64>>::new(values.clone());
+        let min = SegmentTree::<Min<i64>>::new(values.clone());
+        let gcd = SegmentTree::<Gcd<u32>>::new(vec![12, 18, 24, 9]);
+
+        for l in 0..values.len() {
+            for r in l..=values.len() {
+                assert_eq!(values[l..r].iter().sum::<i64>(), sum.query(l..r));
+                assert_eq!(
+                    values[l..r].iter().copied().min().unwrap_or(i64::MAX),
+                    min.query(l..r)
+                );
+            }
+        }
+
+        assert_eq!(6, gcd.query(..3));
+        assert_eq!(3, gcd.query(..));
+
+        sum.set(2, -2);
+        assert_eq!(24, sum.query(..));
+        assert_eq!(&-2, sum.get(2));
+
+        let words: Vec<_> = "abcde".chars().map(String::from).collect();
+        let concat = SegmentTree::<Concat>::new(words);
+
+        assert_eq!("bcd", concat.query(1..=3));
+    }
+
+    #[test]
+    fn find_first() {
+        let sum = SegmentTree::<Sum<u32>>::new(vec![1, 2, 0, 3, 4]);
+
+        assert_eq!(Some(1), sum.find_first(0, |&s| s >= 3));
+        assert_eq!(Some(3), sum.find_first(0, |&s| s >= 4));
+        assert_eq!(Some(4), sum.find_first(2, |&s| s >= 5));
+        assert_eq!(None, sum.find_first(0, |&s| s > 10));
+
+        let max = SegmentTree::<Max<i32>>::new(vec![3, 1, 4, 1, 5, 9, 2, 6]);
+
+        assert_eq!(Some(4), max.find_first(0, |&m| m >= 5));
+        assert_eq!(Some(7), max.find_first(6, |&m| m >= 5));
+    }
+
+    #[test]
+    fn lazy_range_updates() {
+        let mut naive = vec![2i64, 7, 1, 8, 2, 8, 1, 8, 2];
+        let mut sum = LazySegmentTree::<Sum<i64>>::new(naive.clone());
+        let mut max = LazySegmentTree::<Max<i64>>::new(naive.clone());
+
+        let updates = [
+            (1, 5, RangeUpdate::Add(3)),
+            (3, 9, RangeUpdate::Assign(-1)),
+            (0, 4, RangeUpdate::Add(-2)),
+            (2, 3, RangeUpdate::Assign(10)),
+            (4, 8, RangeUpdate::Add(5)),
+        ];
+
+        for &(l, r, update) in &updates {
+            for value in &mut naive[l..r] {
+                *value = match update {
+                    RangeUpdate::Add(a) => *value + a,
+                    RangeUpdate::Assign(x) => x,
+                };
+            }
+
+            sum.update(l..r, update);
+            max.update(l..r, update);
+
+            for l in 0..naive.len() {
+                for r in l + 1..=naive.len() {
+                    assert_eq!(naive[l..r].iter().sum::<i64>(), sum.query(l..r));
+                    assert_eq!(*naive[l..r].iter().max().unwrap(), max.query(l..r));
+                }
+            }
+        }
+
+        sum.set(0, 100);
+        assert_eq!(100, sum.get(0));
+        assert_eq!(naive[1..].iter().sum::<i64>() + 100, sum.query(..));
+
+        let first = naive.iter().position(|&v| v >= 9);
+        assert_eq!(first, max.find_first(0, |&m| m >= 9));
+    }
+
+    #[test]
+    fn lazy_min_against_naive() {
+        let mut rng = Rng::new(27);
+
+        for &len in &[1, 2, 7, 50, 64, 100] {
+            let mut naive: Vec<i64> = (0..len).map(|_| rng.below(100) as i64 - 50).collect();
+            let mut min = LazySegmentTree::<Min<i64>>::new(naive.clone());
+
+            for _ in 0..500 {
+                let l = rng.below(len as u64) as usize;
+                let r = l + 1 + rng.below((len - l) as u64) as usize;
+                let x = rng.below(100) as i64 - 50;
+
+                let update = if rng.below(2) == 0 {
+                    RangeUpdate::Add(x)
+                } else {
+                    RangeUpdate::Assign(x)
+                };
+
+                for value in &mut naive[l..r] {
+                    *value = match update {
+                        RangeUpdate::Add(a) => *value + a,
+                        RangeUpdate::Assign(x) => x,
+                    };
+                }
+
+                min.update(l..r, update);
+
+                let l = rng.below(len as u64) as usize;
+                let r = l + 1 + rng.below((len - l) as u64) as usize;
+
+                assert_eq!(*naive[l..r].iter().min().unwrap(), min.query(l..r));
+                assert_eq!(naive[l], min.get(l));
+
+                let first = naive[l..].iter().position(|&v| v <= x).map(|i| l + i);
+                assert_eq!(first, min.find_first(l, |&m| m <= x));
+            }
+
+            assert_eq!(*naive.iter().min().unwrap(), min.query(..));
+        }
+    }
+}
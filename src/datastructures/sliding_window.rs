@@ -0,0 +1,308 @@
+//! Queues answering aggregate queries over their elements, for sliding windows.
+//!
+//! `MonotonicDeque` keeps the minimum (or maximum) of a FIFO queue. It stores only the elements that can still
+//! become the extreme: a new element discards every older one it beats, since they leave the window before it.
+//! The stored elements are therefore sorted and the extreme is at the front. Every element is pushed and popped
+//! at most once, so all the operations are O(1) amortized.
+//!
+//! `SlidingAggregator` keeps the combination of a FIFO queue under any `Monoid`, which needs no inverse. It uses
+//! two stacks: new elements go on the back stack, which keeps the combination of all its elements, and the front
+//! stack keeps, for every element, the combination of it and all the newer elements of the front stack. When the
+//! front stack is empty, the back stack is moved to it, computing those combinations. Every element is moved once,
+//! so all the operations take O(1) amortized `combine`s.
+
+use std::cmp::Ordering;
+
+use super::deque::Deque;
+use super::segment_tree::Monoid;
+
+pub struct MonotonicDeque<T: Ord> {
+    /// The candidates, with the number of elements pushed before them, from the oldest.
+    candidates: Deque<(usize, T)>,
+    /// The ordering of the extreme with respect to the elements it beats.
+    ordering: Ordering,
+    pushed: usize,
+    popped: usize,
+}
+
+impl<T: Ord> MonotonicDeque<T> {
+    /// A queue keeping its minimum.
+    pub fn min() -> Self {
+        Self::with_ordering(Ordering::Less)
+    }
+
+    /// A queue keeping its maximum.
+    pub fn max() -> Self {
+        Self::with_ordering(Ordering::Greater)
+    }
+
+    fn with_ordering(ordering: Ordering) -> Self {
+        Self {
+            candidates: Deque::new(),
+            ordering,
+            pushed: 0,
+            popped: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.pushed - self.popped
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `value` at the back in O(1) amortized.
+    pub fn push(&mut self, value: T) {
+        // on ties the older element is discarded, it would leave the window first
+        while let Some((_, back)) = self.candidates.back() {
+            if back.cmp(&value) == self.ordering {
+                break;
+            }
+
+            self.candidates.pop_back();
+        }
+
+        self.candidates.push_back((self.pushed, value));
+        self.pushed += 1;
+    }
+
+    /// Removes the oldest element: `None` if the queue is empty, otherwise `Some` of the removed element if it
+    /// was the extreme, or `Some(None)` if it wasn't and is no longer stored.
+    pub fn pop(&mut self) -> Option<Option<T>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let oldest = self.popped;
+        self.popped += 1;
+
+        match self.candidates.front() {
+            Some(&(idx, _)) if idx == oldest => {
+                Some(self.candidates.pop_front().map(|(_, value)| value))
+            }
+            _ => Some(None),
+        }
+    }
+
+    /// The minimum or maximum of the elements.
+    pub fn peek(&self) -> Option<&T> {
+        self.candidates.front().map(|(_, value)| value)
+    }
+
+    pub fn clear(&mut self) {
+        self.candidates.clear();
+        self.popped = self.pushed;
+    }
+}
+
+pub struct SlidingAggregator<M: Monoid> {
+    /// Values with the combination of them and the newer values below them, the oldest at the top.
+    front: Vec<(M::Value, M::Value)>,
+    back: Vec<M::Value>,
+    /// Combination of `back`.
+    back_aggregate: M::Value,
+}
+
+impl<M: Monoid> Default for SlidingAggregator<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M: Monoid> SlidingAggregator<M> {
+    pub fn new() -> Self {
+        Self {
+            front: Vec::new(),
+            back: Vec::new(),
+            back_aggregate: M::identity(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.front.len() + self.back.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds `value` at the back in O(1).
+    pub fn push(&mut self, value: M::Value) {
+        self.back_aggregate = M::combine(&self.back_aggregate, &value);
+        self.back.push(value);
+    }
+
+    /// Removes the oldest value in O(1) amortized.
+    pub fn pop(&mut self) -> Option<M::Value> {
+        if self.front.is_empty() {
+            let mut aggregate = M::identity();
+
+            while let Some(value) = self.back.pop() {
+                aggregate = M::combine(&value, &aggregate);
+                self.front.push((value, aggregate.clone()));
+            }
+
+            self.back_aggregate = M::identity();
+        }
+
+        self.front.pop().map(|(value, _)| value)
+    }
+
+    /// Combination of all the values, from the oldest to the newest.
+    pub fn aggregate(&self) -> M::Value {
+        match self.front.last() {
+            Some((_, front)) => M::combine(front, &self.back_aggregate),
+            None => self.back_aggregate.clone(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.front.clear();
+        self.back.clear();
+        self.back_aggregate = M::identity();
+    }
+}
+
+/// Minimum of every window of `width` consecutive values, in O(n).
+///
+/// Panics if `width` is 0.
+pub fn window_min<T: Ord + Clone>(values: &[T], width: usize) -> Vec<T> {
+    windows(values, width, MonotonicDeque::min())
+}
+
+/// Maximum of every window of `width` consecutive values, in O(n).
+///
+/// Panics if `width` is 0.
+pub fn window_max<T: Ord + Clone>(values: &[T], width: usize) -> Vec<T> {
+    windows(values, width, MonotonicDeque::max())
+}
+
+fn windows<T: Ord + Clone>(values: &[T], width: usize, mut deque: MonotonicDeque<T>) -> Vec<T> {
+    assert!(width > 0, "the windows must not be empty");
+
+    let mut extremes = Vec::with_capacity((values.len() + 1).saturating_sub(width));
+
+    for (idx, value) in values.iter().enumerate() {
+        deque.push(value.clone());
+
+        if idx + 1 >= width {
+            extremes.push(deque.peek().unwrap().clone());
+            deque.pop();
+        }
+    }
+
+    extremes
+}
+
+/// Combination of every window of `width` consecutive values, in O(n) `combine`s.
+///
+/// Panics if `width` is 0.
+pub fn window_aggregate<M: Monoid>(values: &[M::Value], width: usize) -> Vec<M::Value> {
+    assert!(width > 0, "the windows must not be empty");
+
+    let mut aggregator = SlidingAggregator::<M>::new();
+    let mut aggregates = Vec::with_capacity((values.len() + 1).saturating_sub(width));
+
+    for (idx, value) in values.iter().enumerate() {
+        aggregator.push(value.clone());
+
+        if idx + 1 >= width {
+            aggregates.push(aggregator.aggregate());
+            aggregator.pop();
+        }
+    }
+
+    aggregates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{window_aggregate, window_max, window_min, MonotonicDeque, SlidingAggregator};
+    use crate::datastructures::segment_tree::{Monoid, Sum};
+    use crate::random::Rng;
+
+    /// String concatenation, which is not commutative.
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn combine(a: &String, b: &String) -> String {
+            format!("{}{}", a, b)
+        }
+    }
+
+    #[test]
+    fn windows() {
+        let mut rng = Rng::new(40);
+        let values: Vec<i64> = (0..200).map(|_| rng.below(61) as i64 - 30).collect();
+
+        for &width in &[1, 2, 7, 64, 200] {
+            let windows = values.windows(width);
+            let mins: Vec<_> = windows.clone().map(|w| *w.iter().min().unwrap()).collect();
+            let maxs: Vec<_> = windows.clone().map(|w| *w.iter().max().unwrap()).collect();
+            let sums: Vec<_> = windows.map(|w| w.iter().sum::<i64>()).collect();
+
+            assert_eq!(mins, window_min(&values, width));
+            assert_eq!(maxs, window_max(&values, width));
+            assert_eq!(sums, window_aggregate::<Sum<i64>>(&values, width));
+        }
+
+        assert!(window_max(&values, 201).is_empty());
+    }
+
+    #[test]
+    fn monotonic_deque() {
+        let mut deque = MonotonicDeque::min();
+
+        for value in [5, 3, 3, 4, 1] {
+            deque.push(value);
+        }
+
+        assert_eq!(5, deque.len());
+        assert_eq!(Some(&1), deque.peek());
+        assert_eq!(Some(None), deque.pop());
+
+        deque.push(2);
+        for _ in 0..3 {
+            assert_eq!(Some(None), deque.pop());
+        }
+
+        assert_eq!(Some(&1), deque.peek());
+        assert_eq!(Some(Some(1)), deque.pop());
+        assert_eq!(Some(&2), deque.peek());
+        assert_eq!(Some(Some(2)), deque.pop());
+        assert!(deque.is_empty());
+        assert_eq!(None, deque.peek());
+        assert_eq!(None, deque.pop());
+    }
+
+    #[test]
+    fn aggregator_order() {
+        let mut aggregator = SlidingAggregator::<Concat>::new();
+
+        for word in ["a", "b", "c"] {
+            aggregator.push(word.to_string());
+        }
+
+        assert_eq!("abc", aggregator.aggregate());
+        assert_eq!(Some("a".to_string()), aggregator.pop());
+
+        aggregator.push("d".to_string());
+        assert_eq!("bcd", aggregator.aggregate());
+        assert_eq!(Some("b".to_string()), aggregator.pop());
+        assert_eq!(Some("c".to_string()), aggregator.pop());
+        assert_eq!("d", aggregator.aggregate());
+        assert_eq!(1, aggregator.len());
+
+        aggregator.clear();
+        assert_eq!("", aggregator.aggregate());
+        assert_eq!(None, aggregator.pop());
+    }
+}
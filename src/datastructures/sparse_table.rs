@@ -0,0 +1,101 @@
+//! Sparse table for range minimum queries (RMQ) on static arrays.
+//!
+//! `table[k][i]` is the position of the minimum of the `2^k` elements starting at `i`. Level `k` is built from
+//! level `k - 1` by comparing two halves, so the construction takes O(n log n) time and space.
+//!
+//! Since the minimum is idempotent, any range `l..r` is covered by the two (possibly overlapping) blocks of
+//! length `2^k` starting at `l` and ending at `r`, where `2^k` is the greatest power of two not exceeding
+//! `r - l`. Hence every query is answered in O(1).
+
+use std::ops::RangeBounds;
+
+use super::segment_tree::to_bounds;
+
+pub struct SparseTable<T: Ord> {
+    values: Vec<T>,
+    table: Vec<Vec<usize>>,
+}
+
+impl<T: Ord> SparseTable<T> {
+    /// Builds the table in O(n log n).
+    pub fn new(values: Vec<T>) -> Self {
+        let mut table = vec![(0..values.len()).collect::<Vec<_>>()];
+        let mut half = 1;
+
+        while 2 * half <= values.len() {
+            let prev = table.last().unwrap();
+            let level = (0..=values.len() - 2 * half)
+                .map(|i| Self::pick(&values, prev[i], prev[i + half]))
+                .collect();
+
+            table.push(level);
+            half *= 2;
+        }
+
+        Self { values, table }
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    /// Position of the minimum in `range` in O(1). The leftmost one is returned on ties.
+    ///
+    /// Panics if the range is empty.
+    pub fn argmin(&self, range: impl RangeBounds<usize>) -> usize {
+        let (start, end) = to_bounds(range, self.len());
+
+        assert!(start < end, "empty range");
+
+        let k = (usize::BITS - 1 - (end - start).leading_zeros()) as usize;
+
+        Self::pick(
+            &self.values,
+            self.table[k][start],
+            self.table[k][end - (1 << k)],
+        )
+    }
+
+    /// Minimum in `range` in O(1). Panics if the range is empty.
+    pub fn min(&self, range: impl RangeBounds<usize>) -> &T {
+        &self.values[self.argmin(range)]
+    }
+
+    /// Returns the position of the smaller value, `left` on ties.
+    fn pick(values: &[T], left: usize, right: usize) -> usize {
+        if values[right] < values[left] {
+            right
+        } else {
+            left
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SparseTable;
+
+    #[test]
+    fn range_minimum() {
+        let values = vec![5, 2, 8, 2, 9, 1, 7, 3, 6, 4, 1];
+        let table = SparseTable::new(values.clone());
+
+        for l in 0..values.len() {
+            for r in l + 1..=values.len() {
+                let min = *values[l..r].iter().min().unwrap();
+                let pos = l + values[l..r].iter().position(|&v| v == min).unwrap();
+
+                assert_eq!(&min, table.min(l..r));
+                assert_eq!(pos, table.argmin(l..r));
+            }
+        }
+    }
+}
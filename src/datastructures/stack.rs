@@ -0,0 +1,168 @@
+//! LIFO stack built on top of `LinkedList`.
+//!
+//! Every operation works on the front node of the list, so `push`, `pop` and `peek` are O(1)
+//! in the worst case and not just amortized: there is never a buffer to grow.
+//! The price is one allocation per pushed element and poor cache locality compared to a `Vec`.
+
+use std::iter::FromIterator;
+
+use super::linked_list::{self, LinkedList};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stack<T> {
+    list: LinkedList<T>,
+}
+
+impl<T> Default for Stack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Stack<T> {
+    pub fn new() -> Self {
+        Self {
+            list: LinkedList::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.list.is_empty()
+    }
+
+    /// Pushes `value` on top of the stack in O(1).
+    pub fn push(&mut self, value: T) {
+        self.list.push_front(value);
+    }
+
+    /// Removes the top of the stack in O(1).
+    pub fn pop(&mut self) -> Option<T> {
+        self.list.pop_front()
+    }
+
+    pub fn peek(&self) -> Option<&T> {
+        self.list.front()
+    }
+
+    pub fn peek_mut(&mut self) -> Option<&mut T> {
+        self.list.front_mut()
+    }
+
+    /// Iterates from the top to the bottom of the stack.
+    pub fn iter(&self) -> linked_list::Iter<'_, T> {
+        self.list.iter()
+    }
+}
+
+impl<T> FromIterator<T> for Stack<T> {
+    /// Pushes the elements in order, so the last one ends up on top.
+    fn from_iter<It: IntoIterator<Item = T>>(iter: It) -> Self {
+        let mut stack = Self::new();
+        stack.extend(iter);
+        stack
+    }
+}
+
+impl<T> Extend<T> for Stack<T> {
+    fn extend<It: IntoIterator<Item = T>>(&mut self, iter: It) {
+        iter.into_iter().for_each(|value| self.push(value));
+    }
+}
+
+impl<T> IntoIterator for Stack<T> {
+    type Item = T;
+    type IntoIter = linked_list::IntoIter<T>;
+
+    /// Yields the elements in the order they would be popped.
+    fn into_iter(self) -> Self::IntoIter {
+        self.list.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Stack<T> {
+    type Item = &'a T;
+    type IntoIter = linked_list::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::Stack;
+    use crate::random::Rng;
+
+    #[test]
+    fn lifo() {
+        let mut stack: Stack<_> = (0..5).collect();
+
+        assert_eq!(Some(&4), stack.peek());
+        assert_eq!(
+            vec![4, 3, 2, 1, 0],
+            stack.iter().copied().collect::<Vec<_>>()
+        );
+
+        stack.push(5);
+        *stack.peek_mut().unwrap() += 1;
+
+        assert_eq!(Some(6), stack.pop());
+        assert_eq!(Some(4), stack.pop());
+        assert_eq!(vec![3, 2, 1, 0], stack.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn empty() {
+        let mut stack = Stack::<i32>::default();
+
+        assert_eq!(None, stack.peek());
+        assert_eq!(None, stack.peek_mut());
+        assert_eq!(None, stack.pop());
+
+        stack.push(1);
+        assert_eq!(Some(1), stack.pop());
+        assert_eq!(None, stack.pop());
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn against_vec() {
+        let mut rng = Rng::new(26);
+        let mut stack = Stack::new();
+        let mut expected = Vec::new();
+
+        for i in 0..2000 {
+            if rng.below(2) == 0 {
+                stack.push(i);
+                expected.push(i);
+            } else {
+                assert_eq!(expected.pop(), stack.pop());
+            }
+
+            assert_eq!(expected.last(), stack.peek());
+            assert_eq!(expected.len(), stack.len());
+        }
+
+        assert!(expected.iter().rev().eq(stack.iter()));
+        assert!(expected.into_iter().rev().eq(stack));
+    }
+
+    #[test]
+    fn drops_remaining() {
+        let counter = Rc::new(());
+        let stack: Stack<_> = (0..4).map(|_| Rc::clone(&counter)).collect();
+
+        let mut iter = stack.into_iter();
+        iter.next();
+        assert_eq!(4, Rc::strong_count(&counter));
+
+        drop(iter);
+        assert_eq!(1, Rc::strong_count(&counter));
+    }
+}
@@ -0,0 +1,257 @@
+//! Trie (prefix tree) mapping byte strings to values.
+//!
+//! Every node corresponds to the prefix spelled by the edges from the root, and has one child per possible
+//! next byte. Looking up a key of length `m` visits `m` nodes, independently of the number of stored keys.
+//! Each node also counts the keys in its subtree, so the number of keys with a given prefix is found in O(m).
+//!
+//! Children are kept in a `BTreeMap`, hence all the iterators yield the keys in lexicographic order.
+
+use std::collections::BTreeMap;
+
+struct Node<V> {
+    value: Option<V>,
+    /// Number of values in this subtree, including the node itself.
+    count: usize,
+    children: BTreeMap<u8, Node<V>>,
+}
+
+impl<V> Node<V> {
+    fn new() -> Self {
+        Self {
+            value: None,
+            count: 0,
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Removes `key` from this subtree, pruning the nodes that become empty.
+    fn remove(&mut self, key: &[u8]) -> Option<V> {
+        let removed = match key.split_first() {
+            None => self.value.take(),
+            Some((byte, rest)) => {
+                let child = self.children.get_mut(byte)?;
+                let removed = child.remove(rest);
+
+                if child.count == 0 {
+                    self.children.remove(byte);
+                }
+
+                removed
+            }
+        };
+
+        if removed.is_some() {
+            self.count -= 1;
+        }
+
+        removed
+    }
+}
+
+pub struct Trie<V> {
+    root: Node<V>,
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Trie<V> {
+    pub fn new() -> Self {
+        Self { root: Node::new() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.root.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts `value` under `key` in O(m), returning the previous value if the key was present.
+    pub fn insert(&mut self, key: impl AsRef<[u8]>, value: V) -> Option<V> {
+        let key = key.as_ref();
+
+        // Only update the counts once we know whether the key is new.
+        let is_new = self.get(key).is_none();
+        let mut node = &mut self.root;
+
+        for &byte in key {
+            if is_new {
+                node.count += 1;
+            }
+
+            node = node.children.entry(byte).or_insert_with(Node::new);
+        }
+
+        if is_new {
+            node.count += 1;
+        }
+
+        node.value.replace(value)
+    }
+
+    /// Removes `key` in O(m), returning its value if it was present.
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) -> Option<V> {
+        self.root.remove(key.as_ref())
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Option<&V> {
+        self.find(key.as_ref())?.value.as_ref()
+    }
+
+    pub fn get_mut(&mut self, key: impl AsRef<[u8]>) -> Option<&mut V> {
+        let mut node = &mut self.root;
+
+        for byte in key.as_ref() {
+            node = node.children.get_mut(byte)?;
+        }
+
+        node.value.as_mut()
+    }
+
+    pub fn contains_key(&self, key: impl AsRef<[u8]>) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Number of keys starting with `prefix`, in O(m).
+    pub fn count_prefix(&self, prefix: impl AsRef<[u8]>) -> usize {
+        self.find(prefix.as_ref()).map_or(0, |node| node.count)
+    }
+
+    /// The longest key which is a prefix of `key`, together with its value.
+    pub fn longest_prefix<'k>(&self, key: &'k [u8]) -> Option<(&'k [u8], &V)> {
+        let mut node = &self.root;
+        let mut longest = node.value.as_ref().map(|value| (&key[..0], value));
+
+        for (idx, byte) in key.iter().enumerate() {
+            node = match node.children.get(byte) {
+                Some(child) => child,
+                None => break,
+            };
+
+            if let Some(value) = &node.value {
+                longest = Some((&key[..=idx], value));
+            }
+        }
+
+        longest
+    }
+
+    /// Iterates over the keys starting with `prefix` and their values, in lexicographic order.
+    pub fn iter_prefix(&self, prefix: impl AsRef<[u8]>) -> Iter<'_, V> {
+        let prefix = prefix.as_ref();
+        let stack = self
+            .find(prefix)
+            .map(|node| vec![(prefix.to_vec(), node)])
+            .unwrap_or_default();
+
+        Iter { stack }
+    }
+
+    pub fn iter(&self) -> Iter<'_, V> {
+        self.iter_prefix([])
+    }
+
+    fn find(&self, key: &[u8]) -> Option<&Node<V>> {
+        let mut node = &self.root;
+
+        for byte in key {
+            node = node.children.get(byte)?;
+        }
+
+        Some(node)
+    }
+}
+
+/// Pre-order depth-first iterator: a key is always yielded before its extensions.
+pub struct Iter<'a, V> {
+    stack: Vec<(Vec<u8>, &'a Node<V>)>,
+}
+
+impl<'a, V> Iterator for Iter<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((key, node)) = self.stack.pop() {
+            // push in reverse so that the smallest byte is visited first
+            for (&byte, child) in node.children.iter().rev() {
+                let mut child_key = key.clone();
+                child_key.push(byte);
+                self.stack.push((child_key, child));
+            }
+
+            if let Some(value) = &node.value {
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Trie;
+
+    fn keys<V>(iter: impl Iterator<Item = (Vec<u8>, V)>) -> Vec<String> {
+        iter.map(|(key, _)| String::from_utf8(key).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn insert_get_remove() {
+        let mut trie = Trie::new();
+
+        assert_eq!(None, trie.insert("tea", 1));
+        assert_eq!(None, trie.insert("ten", 2));
+        assert_eq!(None, trie.insert("to", 3));
+        assert_eq!(Some(1), trie.insert("tea", 4));
+        assert_eq!(None, trie.insert("", 0));
+
+        assert_eq!(4, trie.len());
+        assert_eq!(Some(&4), trie.get("tea"));
+        assert_eq!(Some(&0), trie.get(""));
+        assert_eq!(None, trie.get("te"));
+
+        *trie.get_mut("to").unwrap() += 10;
+        assert_eq!(Some(&13), trie.get("to"));
+
+        assert_eq!(None, trie.remove("te"));
+        assert_eq!(Some(2), trie.remove("ten"));
+        assert_eq!(None, trie.remove("ten"));
+        assert_eq!(3, trie.len());
+        assert_eq!(1, trie.count_prefix("te"));
+    }
+
+    #[test]
+    fn prefix_queries() {
+        let mut trie = Trie::new();
+
+        for (idx, word) in ["car", "card", "care", "careful", "cat", "dog"]
+            .iter()
+            .enumerate()
+        {
+            trie.insert(word, idx);
+        }
+
+        assert_eq!(5, trie.count_prefix("ca"));
+        assert_eq!(2, trie.count_prefix("care"));
+        assert_eq!(0, trie.count_prefix("cow"));
+        assert_eq!(6, trie.count_prefix(""));
+
+        assert_eq!(vec!["care", "careful"], keys(trie.iter_prefix("care")));
+        assert_eq!(
+            vec!["car", "card", "care", "careful", "cat", "dog"],
+            keys(trie.iter())
+        );
+        assert!(trie.iter_prefix("x").next().is_none());
+
+        assert_eq!(Some((&b"care"[..], &2)), trie.longest_prefix(b"caress"));
+        assert_eq!(Some((&b"car"[..], &0)), trie.longest_prefix(b"car"));
+        assert_eq!(None, trie.longest_prefix(b"ca"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Wavelet matrix: order statistics on the ranges of a static sequence of integers.
+//!
+//! The values are split bit by bit, from the most significant one. Level `l` stores, for every element in the
+//! order of that level, its bit `l` in a `BitVector`, then the elements are stably partitioned with the zeros
+//! first to give the order of the next level. An index range at one level maps to a range at the next level with
+//! two rank queries, so following the bits of a value (or choosing them, to find the k-th smallest) takes O(1)
+//! per level and O(log σ) in total, where σ is the largest value. The matrix takes `n log σ` bits, plus the
+//! overhead of the rank structures.
+
+use std::ops::{Bound, RangeBounds};
+
+use super::bit_vector::BitVector;
+use super::segment_tree::to_bounds;
+
+pub struct WaveletMatrix {
+    /// The bits of every level, from the most significant one.
+    levels: Vec<BitVector>,
+    /// Number of zeros in every level.
+    zeros: Vec<usize>,
+    len: usize,
+}
+
+impl WaveletMatrix {
+    pub fn new(values: &[u64]) -> Self {
+        let max = values.iter().copied().max().unwrap_or(0);
+        let bits = (64 - max.leading_zeros()) as usize;
+
+        let mut levels = Vec::with_capacity(bits);
+        let mut zeros = Vec::with_capacity(bits);
+        let mut current = values.to_vec();
+
+        for bit in (0..bits).rev() {
+            let level: BitVector = current.iter().map(|v| v >> bit & 1 == 1).collect();
+
+            // stable partition, zeros first
+            let (mut next, ones): (Vec<u64>, Vec<u64>) =
+                current.iter().partition(|&&v| v >> bit & 1 == 0);
+            next.extend(ones);
+
+            zeros.push(level.count_zeros());
+            levels.push(level);
+            current = next;
+        }
+
+        Self {
+            levels,
+            zeros,
+            len: values.len(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Value at `idx`.
+    pub fn get(&self, idx: usize) -> u64 {
+        assert!(idx < self.len, "index out of bounds");
+
+        let mut idx = idx;
+        let mut value = 0;
+
+        for (level, bits) in self.levels.iter().enumerate() {
+            let bit = bits.get(idx);
+            value = value << 1 | bit as u64;
+            idx = self.follow(level, bit, idx);
+        }
+
+        value
+    }
+
+    /// The `k`-th smallest value in `range`, counting from 0.
+    pub fn kth_smallest(&self, range: impl RangeBounds<usize>, k: usize) -> Option<u64> {
+        let (mut start, mut end) = to_bounds(range, self.len);
+
+        if k >= end - start {
+            return None;
+        }
+
+        let mut k = k;
+        let mut value = 0;
+
+        for (level, bits) in self.levels.iter().enumerate() {
+            let zeros = bits.rank0(end) - bits.rank0(start);
+            let bit = k >= zeros;
+
+            if bit {
+                k -= zeros;
+            }
+
+            value = value << 1 | bit as u64;
+            start = self.follow(level, bit, start);
+            end = self.follow(level, bit, end);
+        }
+
+        Some(value)
+    }
+
+    /// The `k`-th largest value in `range`, counting from 0.
+    pub fn kth_largest(&self, range: impl RangeBounds<usize>, k: usize) -> Option<u64> {
+        let (start, end) = to_bounds(range, self.len);
+
+        let len = end - start;
+
+        if k >= len {
+            None
+        } else {
+            self.kth_smallest(start..end, len - 1 - k)
+        }
+    }
+
+    /// Number of values smaller than `value` in `range`, i.e. the position `value` would have if the range was
+    /// sorted.
+    pub fn rank(&self, range: impl RangeBounds<usize>, value: u64) -> usize {
+        let (mut start, mut end) = to_bounds(range, self.len);
+
+        // every value has fewer bits than `value`
+        if (64 - value.leading_zeros()) as usize > self.levels.len() {
+            return end - start;
+        }
+
+        let mut smaller = 0;
+
+        for (level, bits) in self.levels.iter().enumerate() {
+            let bit = value >> (self.levels.len() - 1 - level) & 1 == 1;
+
+            if bit {
+                smaller += bits.rank0(end) - bits.rank0(start);
+            }
+
+            start = self.follow(level, bit, start);
+            end = self.follow(level, bit, end);
+        }
+
+        smaller
+    }
+
+    /// Number of occurrences of `value` in `range`.
+    pub fn count(&self, range: impl RangeBounds<usize>, value: u64) -> usize {
+        let (start, end) = to_bounds(range, self.len);
+
+        match value.checked_add(1) {
+            Some(next) => self.rank(start..end, next) - self.rank(start..end, value),
+            None => end - start - self.rank(start..end, value),
+        }
+    }
+
+    /// Number of values in `range` that are within `values`.
+    pub fn range_frequency(
+        &self,
+        range: impl RangeBounds<usize>,
+        values: impl RangeBounds<u64>,
+    ) -> usize {
+        let (start, end) = to_bounds(range, self.len);
+
+        // number of values smaller than the bound, or all of them for a bound past `u64::MAX`
+        let below = |bound: Option<u64>| bound.map_or(end - start, |v| self.rank(start..end, v));
+
+        let low = match values.start_bound() {
+            Bound::Included(&v) => Some(v),
+            Bound::Excluded(&v) => v.checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+
+        let high = match values.end_bound() {
+            Bound::Included(&v) => v.checked_add(1),
+            Bound::Excluded(&v) => Some(v),
+            Bound::Unbounded => None,
+        };
+
+        below(high).saturating_sub(below(low))
+    }
+
+    /// Position at the next level of the element at `idx`, or of the range boundary `idx`, given its bit.
+    fn follow(&self, level: usize, bit: bool, idx: usize) -> usize {
+        if bit {
+            self.zeros[level] + self.levels[level].rank1(idx)
+        } else {
+            self.levels[level].rank0(idx)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::WaveletMatrix;
+    use crate::random::Rng;
+
+    #[test]
+    fn order_statistics() {
+        let mut rng = Rng::new(38);
+        let values: Vec<u64> = (0..300).map(|_| rng.below(97)).collect();
+        let matrix = WaveletMatrix::new(&values);
+
+        assert_eq!(values.len(), matrix.len());
+        assert!((0..values.len()).all(|i| matrix.get(i) == values[i]));
+
+        for (start, end) in [(0, 300), (10, 11), (17, 150), (299, 300), (50, 50)] {
+            let mut sorted = values[start..end].to_vec();
+            sorted.sort_unstable();
+
+            for (k, &value) in sorted.iter().enumerate() {
+                assert_eq!(Some(value), matrix.kth_smallest(start..end, k));
+                assert_eq!(
+                    Some(value),
+                    matrix.kth_largest(start..end, sorted.len() - 1 - k)
+                );
+            }
+
+            assert_eq!(None, matrix.kth_smallest(start..end, sorted.len()));
+
+            for value in [0, 1, 42, 96, 97, 1000, u64::MAX] {
+                let smaller = sorted.iter().filter(|&&v| v < value).count();
+                let equal = sorted.iter().filter(|&&v| v == value).count();
+
+                assert_eq!(smaller, matrix.rank(start..end, value));
+                assert_eq!(equal, matrix.count(start..end, value));
+            }
+
+            let in_values = sorted.iter().filter(|&&v| (20..=40).contains(&v)).count();
+            assert_eq!(in_values, matrix.range_frequency(start..end, 20..=40));
+            assert_eq!(sorted.len(), matrix.range_frequency(start..end, ..));
+            assert_eq!(
+                0,
+                matrix.range_frequency(start..end, (Bound::Included(40), Bound::Excluded(20)))
+            );
+        }
+    }
+
+    #[test]
+    fn large_values() {
+        let values = [u64::MAX, 0, u64::MAX - 1, 1 << 40];
+        let matrix = WaveletMatrix::new(&values);
+
+        assert_eq!(Some(1 << 40), matrix.kth_smallest(.., 1));
+        assert_eq!(Some(u64::MAX), matrix.kth_largest(.., 0));
+        assert_eq!(1, matrix.count(.., u64::MAX));
+        assert_eq!(2, matrix.range_frequency(.., u64::MAX - 1..));
+        assert_eq!(Some(0), WaveletMatrix::new(&[0, 0]).kth_smallest(.., 1));
+    }
+}
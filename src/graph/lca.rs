@@ -0,0 +1,306 @@
+//! Lowest common ancestor (LCA) queries on rooted trees.
+//!
+//! The tree can be stored in any `AdjacencyStructure`, either with edges from parents to children or with
+//! edges in both directions: a depth-first search from the root ignores the vertices it already visited.
+//! Vertices that are not reachable from the root have no ancestors, and queries involving them return `None`.
+
+use crate::datastructures::sparse_table::SparseTable;
+
+use super::structure::{AdjacencyStructure, Direction};
+
+/// Result of an iterative depth-first search from the root.
+struct Traversal {
+    parent: Vec<Option<usize>>,
+    depth: Vec<Option<usize>>,
+    /// Vertices in the order they're visited, including every return to a parent.
+    euler: Vec<usize>,
+    /// Position of the first occurrence of each vertex in `euler`.
+    first: Vec<Option<usize>>,
+}
+
+impl Traversal {
+    fn new<'a, D: Direction, A: AdjacencyStructure<'a, D>>(tree: &'a A, root: usize) -> Self {
+        let vertices = tree.count_vertices();
+        let mut traversal = Self {
+            parent: vec![None; vertices],
+            depth: vec![None; vertices],
+            euler: Vec::with_capacity(2 * vertices),
+            first: vec![None; vertices],
+        };
+
+        let root_adjacency = tree.adjacency_iter(root).expect("root is not in the tree");
+
+        traversal.depth[root] = Some(0);
+        traversal.first[root] = Some(0);
+        traversal.euler.push(root);
+
+        let mut stack = vec![(root, root_adjacency)];
+
+        while let Some((vertex, adjacent)) = stack.last_mut() {
+            let vertex = *vertex;
+            let depth = &traversal.depth;
+            let child = adjacent.find(|&v| depth[v].is_none());
+
+            match child {
+                Some(child) => {
+                    traversal.parent[child] = Some(vertex);
+                    traversal.depth[child] = traversal.depth[vertex].map(|d| d + 1);
+                    traversal.first[child] = Some(traversal.euler.len());
+                    traversal.euler.push(child);
+
+                    stack.push((child, tree.adjacency_iter(child).unwrap()));
+                }
+                None => {
+                    stack.pop();
+
+                    if let Some(&(parent, _)) = stack.last() {
+                        traversal.euler.push(parent);
+                    }
+                }
+            }
+        }
+
+        traversal
+    }
+}
+
+/// LCA via Euler tour and range minimum query.
+///
+/// The LCA of `u` and `v` is the shallowest vertex visited between the first visits of `u` and `v`.
+/// With a `SparseTable` over the depths of the tour, the preprocessing takes O(n log n) and each query O(1).
+pub struct EulerTourLca {
+    depth: Vec<Option<usize>>,
+    first: Vec<Option<usize>>,
+    /// `(depth, vertex)` for each step of the tour.
+    tour: SparseTable<(usize, usize)>,
+}
+
+impl EulerTourLca {
+    /// Preprocesses the vertices reachable from `root`.
+    ///
+    /// Panics if `root` is not in the tree.
+    pub fn new<'a, D: Direction, A: AdjacencyStructure<'a, D>>(tree: &'a A, root: usize) -> Self {
+        let Traversal {
+            depth,
+            euler,
+            first,
+            ..
+        } = Traversal::new(tree, root);
+
+        let tour = euler.into_iter().map(|v| (depth[v].unwrap(), v)).collect();
+
+        Self {
+            depth,
+            first,
+            tour: SparseTable::new(tour),
+        }
+    }
+
+    /// Distance from the root, `None` if `vertex` is not reachable from it.
+    pub fn depth(&self, vertex: usize) -> Option<usize> {
+        self.depth.get(vertex).copied().flatten()
+    }
+
+    /// Lowest common ancestor of `u` and `v` in O(1).
+    pub fn lca(&self, u: usize, v: usize) -> Option<usize> {
+        let (a, b) = (self.first(u)?, self.first(v)?);
+        let (start, end) = (a.min(b), a.max(b));
+
+        Some(self.tour.min(start..=end).1)
+    }
+
+    /// Number of edges on the path between `u` and `v`.
+    pub fn distance(&self, u: usize, v: usize) -> Option<usize> {
+        let lca = self.lca(u, v)?;
+
+        Some(self.depth(u)? + self.depth(v)? - 2 * self.depth(lca)?)
+    }
+
+    fn first(&self, vertex: usize) -> Option<usize> {
+        self.first.get(vertex).copied().flatten()
+    }
+}
+
+/// LCA via binary lifting.
+///
+/// `up[k][v]` is the `2^k`-th ancestor of `v` (the root is its own ancestor). Preprocessing takes O(n log n)
+/// and each query O(log n), but it also answers k-th ancestor queries.
+pub struct BinaryLiftingLca {
+    depth: Vec<Option<usize>>,
+    up: Vec<Vec<usize>>,
+}
+
+impl BinaryLiftingLca {
+    /// Preprocesses the vertices reachable from `root`.
+    ///
+    /// Panics if `root` is not in the tree.
+    pub fn new<'a, D: Direction, A: AdjacencyStructure<'a, D>>(tree: &'a A, root: usize) -> Self {
+        let Traversal { parent, depth, .. } = Traversal::new(tree, root);
+
+        let first: Vec<_> = parent
+            .iter()
+            .enumerate()
+            .map(|(v, p)| p.unwrap_or(v))
+            .collect();
+        let levels = (usize::BITS - first.len().leading_zeros()).max(1) as usize;
+        let mut up = vec![first];
+
+        for k in 1..levels {
+            let prev = &up[k - 1];
+            let level = prev.iter().map(|&mid| prev[mid]).collect();
+
+            up.push(level);
+        }
+
+        Self { depth, up }
+    }
+
+    /// Distance from the root, `None` if `vertex` is not reachable from it.
+    pub fn depth(&self, vertex: usize) -> Option<usize> {
+        self.depth.get(vertex).copied().flatten()
+    }
+
+    /// The `k`-th ancestor of `vertex` (the 0-th is `vertex` itself), `None` if it's above the root.
+    pub fn kth_ancestor(&self, vertex: usize, k: usize) -> Option<usize> {
+        if k > self.depth(vertex)? {
+            return None;
+        }
+
+        Some(self.lift(vertex, k))
+    }
+
+    /// Lowest common ancestor of `u` and `v` in O(log n).
+    pub fn lca(&self, u: usize, v: usize) -> Option<usize> {
+        let (du, dv) = (self.depth(u)?, self.depth(v)?);
+
+        // bring both vertices to the same depth
+        let (mut u, mut v) = if du >= dv {
+            (self.lift(u, du - dv), v)
+        } else {
+            (u, self.lift(v, dv - du))
+        };
+
+        if u == v {
+            return Some(u);
+        }
+
+        // jump as high as possible while staying below the LCA
+        for level in self.up.iter().rev() {
+            if level[u] != level[v] {
+                u = level[u];
+                v = level[v];
+            }
+        }
+
+        Some(self.up[0][u])
+    }
+
+    /// Number of edges on the path between `u` and `v`.
+    pub fn distance(&self, u: usize, v: usize) -> Option<usize> {
+        let lca = self.lca(u, v)?;
+
+        Some(self.depth(u)? + self.depth(v)? - 2 * self.depth(lca)?)
+    }
+
+    fn lift(&self, mut vertex: usize, k: usize) -> usize {
+        for (bit, level) in self.up.iter().enumerate() {
+            if k & (1 << bit) != 0 {
+                vertex = level[vertex];
+            }
+        }
+
+        vertex
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::graph::structure::{AdjacencyList, AdjacencyStructure, Directed};
+
+    use super::{BinaryLiftingLca, EulerTourLca};
+
+    //         0
+    //       / | \
+    //      1  2  3
+    //     / \     \
+    //    4   5     6
+    //   /         / \
+    //  7         8   9
+    const EDGES: &[(usize, usize)] = &[
+        (0, 1),
+        (0, 2),
+        (0, 3),
+        (1, 4),
+        (1, 5),
+        (3, 6),
+        (4, 7),
+        (6, 8),
+        (6, 9),
+    ];
+
+    const QUERIES: &[(usize, usize, usize)] = &[
+        (7, 5, 1),
+        (7, 4, 4),
+        (8, 9, 6),
+        (7, 9, 0),
+        (2, 2, 2),
+        (5, 6, 0),
+        (9, 3, 3),
+    ];
+
+    fn tree(both_directions: bool) -> AdjacencyList<Directed> {
+        let mut tree = AdjacencyList::new();
+
+        // vertex 10 is not connected to the tree
+        for _ in 0..11 {
+            tree.insert_vertex();
+        }
+
+        for &(parent, child) in EDGES {
+            tree.insert_edge(parent, child);
+
+            if both_directions {
+                tree.insert_edge(child, parent);
+            }
+        }
+
+        tree
+    }
+
+    #[test]
+    fn euler_tour() {
+        for &both_directions in &[false, true] {
+            let tree = tree(both_directions);
+            let lca = EulerTourLca::new(&tree, 0);
+
+            for &(u, v, expected) in QUERIES {
+                assert_eq!(Some(expected), lca.lca(u, v), "lca({}, {})", u, v);
+                assert_eq!(Some(expected), lca.lca(v, u), "lca({}, {})", v, u);
+            }
+
+            assert_eq!(Some(6), lca.distance(7, 8));
+            assert_eq!(None, lca.lca(10, 0));
+            assert_eq!(None, lca.depth(10));
+        }
+    }
+
+    #[test]
+    fn binary_lifting() {
+        for &both_directions in &[false, true] {
+            let tree = tree(both_directions);
+            let lca = BinaryLiftingLca::new(&tree, 0);
+
+            for &(u, v, expected) in QUERIES {
+                assert_eq!(Some(expected), lca.lca(u, v), "lca({}, {})", u, v);
+                assert_eq!(Some(expected), lca.lca(v, u), "lca({}, {})", v, u);
+            }
+
+            assert_eq!(Some(3), lca.depth(8));
+            assert_eq!(Some(6), lca.kth_ancestor(8, 1));
+            assert_eq!(Some(0), lca.kth_ancestor(8, 3));
+            assert_eq!(None, lca.kth_ancestor(8, 4));
+            assert_eq!(Some(4), lca.distance(5, 6));
+            assert_eq!(None, lca.lca(0, 10));
+        }
+    }
+}
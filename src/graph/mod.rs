@@ -1,4 +1,5 @@
 pub mod bellman_ford;
 pub mod dijkstra;
 pub mod floyd_warshall;
+pub mod lca;
 pub mod structure;
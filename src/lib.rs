@@ -1,5 +1,6 @@
 pub mod datastructures;
 pub mod graph;
+pub mod random;
 pub mod s3;
 pub mod s4;
 pub mod s6;
@@ -0,0 +1,40 @@
+//! Deterministic pseudo-random numbers from a SplitMix64 generator.
+//!
+//! It's not meant for cryptography or statistics, only to get reproducible inputs for the tests and benchmarks,
+//! and reproducible random choices such as `PivotStrategy::Random`: the same seed always gives the same numbers.
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..bound`, which must not be 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+
+    /// A float in `[0, 1)`.
+    pub fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Shuffles the sequence uniformly with the Fisher-Yates algorithm.
+    pub fn shuffle<T>(&mut self, seq: &mut [T]) {
+        for end in (1..seq.len()).rev() {
+            seq.swap(end, self.below(end as u64 + 1) as usize);
+        }
+    }
+}
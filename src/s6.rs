@@ -1,3 +1,5 @@
+use crate::datastructures::cache::{Cache, LruCache, Memoizer};
+
 /// Shitty recursive version of `F(i) = F(i - 1) + F(i - 2) + F(i - 3)`, `F(1) = F(2) = F(3) = 1`.
 ///
 /// Top-down recursive
@@ -11,34 +13,30 @@ fn dp_recursive(num: usize) -> usize {
     }
 }
 
-/// Top-down memoized recursive solution
+/// Top-down memoized recursive solution, in constant memory besides the recursion: see `dp_memoized_with`.
 fn dp_memoized(num: usize) -> usize {
-    if num == 0 {
-        0
-    } else if num <= 3 {
-        1
-    } else {
-        let mut memo = vec![0; 1 + num as usize];
-
-        memo[1] = 1;
-        memo[2] = 1;
-        memo[3] = 1;
-
-        fn recursive(num: usize, memo: &mut [usize]) -> usize {
-            let element = memo[num];
-            if element == 0 {
-                let val =
-                    recursive(num - 1, memo) + recursive(num - 2, memo) + recursive(num - 3, memo);
-
-                memo[num] = val;
-            }
+    dp_memoized_with(num, LruCache::new(4))
+}
 
-            memo[num]
+/// Top-down memoized recursive solution, keeping only the values that fit in `cache`.
+///
+/// A least recently used cache of 4 values is enough to compute every value once.
+pub fn dp_memoized_with<C: Cache<usize, usize>>(num: usize, cache: C) -> usize {
+    fn recursive<C: Cache<usize, usize>>(num: usize, memo: &mut Memoizer<C>) -> usize {
+        if num == 0 {
+            0
+        } else if num <= 3 {
+            1
+        } else {
+            memo.get_or_insert_with(num, |memo| {
+                recursive(num - 1, memo) + recursive(num - 2, memo) + recursive(num - 3, memo)
+            })
         }
-
-        recursive(num, &mut memo)
     }
+
+    recursive(num, &mut Memoizer::new(cache))
 }
+
 fn dp_bottom_up(num: usize) -> usize {
     let mut table = Vec::with_capacity(num + 1);
 
@@ -72,6 +70,23 @@ mod tests {
         }
     }
 
+    #[test]
+    fn memoized_with() {
+        use crate::datastructures::cache::{LfuCache, LruCache};
+
+        for &(num, expected) in DATASET {
+            assert_eq!(super::dp_memoized_with(num, LruCache::new(4)), expected);
+            assert_eq!(super::dp_memoized_with(num, LfuCache::new(4)), expected);
+        }
+
+        for i in 0..50 {
+            assert_eq!(
+                super::dp_memoized_with(i, LruCache::new(4)),
+                super::dp_bottom_up(i)
+            );
+        }
+    }
+
     #[test]
     fn bottom_up() {
         for &(num, expected) in DATASET {
@@ -2,111 +2,342 @@
 //! that have been discussed in the lecture.
 //! Each algorithm is briefly explained and its runtime is analyzed.
 
-pub mod bubble_sort {}
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
 
-pub mod selection_sort {}
+    /// Ordered by the key only, the second field tells equal elements apart to check stability.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Entry(pub u32, pub u32);
 
-pub mod insertion_sort {}
+    impl PartialOrd for Entry {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
 
-pub mod heap_sort {
-    use std::cmp::Ord;
-    use std::fmt::Debug;
+    impl Ord for Entry {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
 
-    /// Implementation of a custom MinHeap to show how it works internally.
-    struct MinHeap<'s, I> {
-        len: usize,
-        seq: &'s mut [I],
+    /// Checks a `*_by` and `*_by_key` pair: sorts in descending order with a comparator, then by the second
+    /// field, which is unique.
+    pub fn check_by(
+        mut sort_by: impl FnMut(&mut [Entry], fn(&Entry, &Entry) -> Ordering),
+        mut sort_by_key: impl FnMut(&mut [Entry], fn(&Entry) -> u32),
+    ) {
+        let mut sequence: Vec<Entry> = (0..100).map(|i| Entry((i * 37) % 10, i)).collect();
+
+        sort_by(&mut sequence, |a, b| b.cmp(a));
+        assert!(sequence.windows(2).all(|w| w[0] >= w[1]));
+
+        sort_by_key(&mut sequence, |entry| entry.1);
+        assert!((0..100).eq(sequence.iter().map(|entry| entry.1)));
     }
 
-    impl<'s, I: Ord + Copy> MinHeap<'s, I> {
-        /// Builds a min heap by moving the elements in the given sequence.
-        pub fn new(seq: &'s mut [I]) -> Self {
-            let len = seq.len();
-            let mut heap = Self { seq, len };
+    /// Checks a sort on elements that own heap memory, with duplicates.
+    pub fn check_strings(mut sort: impl FnMut(&mut [String])) {
+        let mut sequence: Vec<String> = (0..200).map(|i| ((i * 37) % 50).to_string()).collect();
+        let mut expected = sequence.clone();
+        expected.sort();
 
-            heap.build_heap();
+        sort(&mut sequence);
 
-            heap
-        }
+        assert_eq!(expected, sequence);
+    }
+}
+
+pub mod bubble_sort {
+    use std::cmp::{Ord, Ordering};
+
+    /// Bubble sort: every pass swaps the adjacent pairs that are out of order, which moves the largest
+    /// element of the unsorted prefix to its end.
+    ///
+    /// Everything after the last swap of a pass is already in place, so the next pass stops there, and the
+    /// sort ends after a pass without swaps. The running time is `O(n^2)` in the worst case and `O(n)` on a
+    /// sorted sequence. It's stable and in place.
+    pub fn bubble_sort<I: Ord>(seq: &mut [I]) {
+        bubble_sort_with(seq, &mut I::cmp);
+    }
 
-        fn build_heap(&mut self) {
-            // if 0- or 1-length it's already built
-            if self.len > 1 {
-                // last non-leaf node
-                let start = (self.len / 2) - 1;
+    /// Sorts with the comparator function `compare`, like `slice::sort_by`.
+    pub fn bubble_sort_by<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], mut compare: F) {
+        bubble_sort_with(seq, &mut compare);
+    }
+
+    /// Sorts by the key extracted with `key`, like `slice::sort_by_key`.
+    pub fn bubble_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(seq: &mut [I], mut key: F) {
+        bubble_sort_with(seq, &mut |a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    fn bubble_sort_with<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], compare: &mut F) {
+        let mut unsorted = seq.len();
 
-                for idx in (0..=start).rev() {
-                    self.min_heapify(idx);
+        while unsorted > 1 {
+            let mut last_swap = 0;
+
+            for idx in 1..unsorted {
+                if compare(&seq[idx - 1], &seq[idx]) == Ordering::Greater {
+                    seq.swap(idx - 1, idx);
+                    last_swap = idx;
                 }
             }
+
+            // no swaps: the whole prefix is sorted
+            unsorted = last_swap;
         }
+    }
 
-        fn min_heapify(&mut self, idx: usize) {
-            let mut checking = idx;
+    #[cfg(test)]
+    mod tests {
+        use crate::sorting_algorithms::tests::check_by;
 
-            loop {
-                let parent = checking;
-                let left = 2 * checking + 1;
-                let right = 2 * checking + 2;
+        #[test]
+        fn correct() {
+            let sequence = &mut [9, 8, 2, 3, 1, 5, 6, 4, 7, 2];
+            let expected = &[1, 2, 2, 3, 4, 5, 6, 7, 8, 9];
 
-                let mut min_idx = parent;
+            super::bubble_sort(sequence);
 
-                if left < self.len && self.seq[min_idx] > self.seq[left] {
-                    min_idx = left;
-                }
+            assert_eq!(expected, sequence);
+        }
 
-                if right < self.len && self.seq[min_idx] > self.seq[right] {
-                    min_idx = right;
-                }
+        #[test]
+        fn correct_small() {
+            let set: &mut [(&mut [i64], &[i64])] = &mut [
+                (&mut [], &[]),
+                (&mut [1], &[1]),
+                (&mut [2, 1], &[1, 2]),
+                (&mut [1, 2, 3], &[1, 2, 3]),
+            ];
 
-                // min-heap property broken, must swap
-                if min_idx != parent {
-                    self.seq.swap(parent, min_idx);
-                    checking = min_idx;
-                } else {
-                    // this parent is fine and thus also all its children are.
-                    break;
-                }
+            for (seq, expected) in set.iter_mut() {
+                super::bubble_sort(seq);
+
+                assert_eq!(expected, seq);
             }
         }
 
-        pub fn remove_min(&mut self) -> Option<I> {
-            if self.len > 0 {
-                let min = self.seq[0];
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::bubble_sort_by(seq, compare),
+                |seq, key| super::bubble_sort_by_key(seq, key),
+            );
+        }
+    }
+}
+
+pub mod selection_sort {
+    use std::cmp::{Ord, Ordering};
+
+    /// Selection sort: the minimum of the unsorted suffix is selected and moved to its start.
+    ///
+    /// The minimum is the first of the equal ones and it's moved by shifting the elements before it by one
+    /// position instead of swapping, so the sort is stable. Finding the minimum takes `O(n)` comparisons and
+    /// shifting `O(n)` moves, resulting in `O(n^2)` time in every case, in place.
+    pub fn selection_sort<I: Ord>(seq: &mut [I]) {
+        selection_sort_with(seq, &mut I::cmp);
+    }
+
+    /// Sorts with the comparator function `compare`, like `slice::sort_by`.
+    pub fn selection_sort_by<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], mut compare: F) {
+        selection_sort_with(seq, &mut compare);
+    }
 
-                // swap head (min) with the last element
-                self.seq[0] = self.seq[self.len - 1];
-                self.len -= 1;
+    /// Sorts by the key extracted with `key`, like `slice::sort_by_key`.
+    pub fn selection_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(seq: &mut [I], mut key: F) {
+        selection_sort_with(seq, &mut |a: &I, b: &I| key(a).cmp(&key(b)));
+    }
 
-                // Fix heap to restore min-heap property
-                self.min_heapify(0);
+    fn selection_sort_with<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], compare: &mut F) {
+        for start in 0..seq.len() {
+            let mut min = start;
 
-                Some(min)
-            } else {
-                None
+            for idx in start + 1..seq.len() {
+                if compare(&seq[idx], &seq[min]) == Ordering::Less {
+                    min = idx;
+                }
             }
+
+            seq[start..=min].rotate_right(1);
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::sorting_algorithms::tests::{check_by, Entry};
+
+        #[test]
+        fn correct() {
+            let sequence = &mut [9, 8, 2, 3, 1, 5, 6, 4, 7, 2];
+            let expected = &[1, 2, 2, 3, 4, 5, 6, 7, 8, 9];
+
+            super::selection_sort(sequence);
+
+            assert_eq!(expected, sequence);
+        }
+
+        #[test]
+        fn stable() {
+            let sequence = &mut [Entry(2, 0), Entry(1, 1), Entry(2, 2), Entry(1, 3)];
+            let expected = &[Entry(1, 1), Entry(1, 3), Entry(2, 0), Entry(2, 2)];
+
+            super::selection_sort(sequence);
+
+            assert!(expected
+                .iter()
+                .zip(sequence.iter())
+                .all(|(a, b)| a.1 == b.1));
+        }
+
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::selection_sort_by(seq, compare),
+                |seq, key| super::selection_sort_by_key(seq, key),
+            );
+        }
+    }
+}
+
+pub mod insertion_sort {
+    use std::cmp::{Ord, Ordering};
+
+    /// Binary insertion sort: every element is inserted in the sorted prefix before it.
+    ///
+    /// The position is found with a binary search, after all the equal elements so that the sort is stable,
+    /// then the greater elements are shifted by one. It performs `O(n log n)` comparisons but `O(n^2)` moves
+    /// in the worst case, and `O(n)` moves on a sorted sequence. It's in place.
+    pub fn insertion_sort<I: Ord>(seq: &mut [I]) {
+        insertion_sort_with(seq, &mut I::cmp);
+    }
+
+    /// Sorts with the comparator function `compare`, like `slice::sort_by`.
+    pub fn insertion_sort_by<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], mut compare: F) {
+        insertion_sort_with(seq, &mut compare);
+    }
+
+    /// Sorts by the key extracted with `key`, like `slice::sort_by_key`.
+    pub fn insertion_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(seq: &mut [I], mut key: F) {
+        insertion_sort_with(seq, &mut |a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    fn insertion_sort_with<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], compare: &mut F) {
+        insertion_sort_from(seq, 1, compare);
+    }
+
+    /// Inserts the elements from `sorted` on in the prefix `..sorted`, which must be sorted already.
+    pub(crate) fn insertion_sort_from<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        sorted: usize,
+        compare: &mut F,
+    ) {
+        for idx in sorted.max(1)..seq.len() {
+            let (sorted, rest) = seq.split_at(idx);
+            let pos = sorted.partition_point(|el| compare(el, &rest[0]) != Ordering::Greater);
+
+            seq[pos..=idx].rotate_right(1);
         }
     }
 
-    /// Implementation of the heap sort algorithm.
-    /// It builds a min-heap in O(n) and then it extracts each value in O(log n), resulting in O(n log n) time total.
-    /// Uses auxiliary O(n) space.
-    pub fn heap_sort<I: Ord + Debug + Copy>(seq: &mut [I]) {
-        let mut temp = Vec::with_capacity(seq.len());
+    #[cfg(test)]
+    mod tests {
+        use crate::sorting_algorithms::tests::{check_by, Entry};
+
+        #[test]
+        fn correct() {
+            let sequence = &mut [9, 8, 2, 3, 1, 5, 6, 4, 7, 2];
+            let expected = &[1, 2, 2, 3, 4, 5, 6, 7, 8, 9];
+
+            super::insertion_sort(sequence);
+
+            assert_eq!(expected, sequence);
+        }
+
+        #[test]
+        fn correct_small() {
+            let set: &mut [(&mut [i64], &[i64])] = &mut [
+                (&mut [], &[]),
+                (&mut [1], &[1]),
+                (&mut [2, 1], &[1, 2]),
+                (&mut [3, 2, 1], &[1, 2, 3]),
+            ];
+
+            for (seq, expected) in set.iter_mut() {
+                super::insertion_sort(seq);
+
+                assert_eq!(expected, seq);
+            }
+        }
+
+        #[test]
+        fn stable() {
+            let mut sequence: Vec<Entry> = (0..100).map(|i| Entry((i * 37) % 10, i)).collect();
+            let mut expected = sequence.clone();
+            expected.sort();
+
+            super::insertion_sort(&mut sequence);
 
-        // build min heap in O(n)
-        let mut heap = MinHeap::new(seq);
+            assert!(expected
+                .iter()
+                .zip(sequence.iter())
+                .all(|(a, b)| a.1 == b.1));
+        }
 
-        // Remove every element fron the min heap in O(n log n)
-        while let Some(removed) = heap.remove_min() {
-            temp.push(removed);
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::insertion_sort_by(seq, compare),
+                |seq, key| super::insertion_sort_by_key(seq, key),
+            );
         }
+    }
+}
+
+pub mod heap_sort {
+    use std::cmp::{Ord, Ordering};
+
+    use crate::datastructures::heap;
+
+    /// Implementation of the heap sort algorithm, in place.
+    /// It builds a max-heap on the sequence in O(n) with Floyd's bottom-up construction. Then it repeatedly swaps
+    /// the max with the last element of the heap, which shrinks by one, and sifts the new top down in O(log n),
+    /// resulting in O(n log n) time total. The sorted suffix grows from the end and no auxiliary space is used.
+    ///
+    /// The new top comes from the bottom of the heap and likely goes back near the leaves, so it's sifted with
+    /// Floyd's bottom-up `sift_down`, which performs about `n log n` comparisons instead of `2 n log n`.
+    pub fn heap_sort<I: Ord>(seq: &mut [I]) {
+        heap_sort_with(seq, &mut I::cmp);
+    }
+
+    /// Sorts with the comparator function `compare`, like `slice::sort_by`.
+    pub fn heap_sort_by<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], mut compare: F) {
+        heap_sort_with(seq, &mut compare);
+    }
+
+    /// Sorts by the key extracted with `key`, like `slice::sort_by_key`.
+    pub fn heap_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(seq: &mut [I], mut key: F) {
+        heap_sort_with(seq, &mut |a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    fn heap_sort_with<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], compare: &mut F) {
+        // build max heap in O(n)
+        heap::heapify(seq, &mut *compare);
 
-        seq.copy_from_slice(&temp);
+        // Move every max after the heap in O(n log n)
+        for end in (1..seq.len()).rev() {
+            seq.swap(0, end);
+            heap::sift_down_bottom_up(&mut seq[..end], &mut *compare, 0);
+        }
     }
 
     #[cfg(test)]
     mod tests {
+        use crate::sorting_algorithms::tests::{check_by, check_strings};
+
         #[test]
         fn correct_simple() {
             let sequence = &mut [8, 8, 4, 3, 2, 1, 9, 5];
@@ -132,73 +363,244 @@ pub mod heap_sort {
                 assert_eq!(expected, seq);
             }
         }
+
+        #[test]
+        fn heap_data() {
+            check_strings(super::heap_sort);
+        }
+
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::heap_sort_by(seq, compare),
+                |seq, key| super::heap_sort_by_key(seq, key),
+            );
+        }
     }
 }
 
 pub mod quick_sort {
-    use std::cmp::Ord;
+    use std::cmp::{Ord, Ordering};
+
+    use super::insertion_sort::insertion_sort_by;
+    use super::intro_sort::{median_of_three, ninther};
+    use super::selection::select_with;
+    use crate::random::Rng;
+
+    /// How the pivot of every partition is chosen.
+    ///
+    /// A pivot splitting the sequence in two halves of similar length gives the best case, `O(n log n)`. One
+    /// that is always the smallest or greatest element gives the worst case, `O(n^2)`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum PivotStrategy {
+        /// The first element: sorted and reversed sequences are the worst case.
+        First,
+        /// The last element: sorted and reversed sequences are the worst case.
+        Last,
+        /// The middle element, the default: sorted and reversed sequences are the best case.
+        #[default]
+        Middle,
+        /// A uniformly random element, from a generator with the given seed. The expected time is `O(n log n)`
+        /// on every sequence.
+        Random(u64),
+        /// The median of the first, middle and last elements: sorted sequences are the best case, but some
+        /// sequences built for it are still the worst case.
+        MedianOfThree,
+        /// The median of the medians of three triples of elements around the quartiles and the middle, which
+        /// resists more inputs than the median of three.
+        Ninther,
+        /// The median of the medians of groups of 5 elements. At least 30% of the elements are on each side, so
+        /// the worst case is `O(n log n)`, but it's slower than the other strategies on average.
+        MedianOfMedians,
+    }
+
+    /// A pivot strategy with the state of its random generator.
+    pub(crate) struct PivotSelector {
+        strategy: PivotStrategy,
+        rng: Rng,
+    }
+
+    impl PivotSelector {
+        pub(crate) fn new(strategy: PivotStrategy) -> Self {
+            let seed = match strategy {
+                PivotStrategy::Random(seed) => seed,
+                _ => 0,
+            };
+
+            Self {
+                strategy,
+                rng: Rng::new(seed),
+            }
+        }
+
+        /// Index of the pivot of the sequence, which must not be empty. Only median of medians moves elements.
+        pub(crate) fn select<I, F: FnMut(&I, &I) -> Ordering>(
+            &mut self,
+            sequence: &mut [I],
+            compare: &mut F,
+        ) -> usize {
+            let len = sequence.len();
+
+            match self.strategy {
+                PivotStrategy::First => 0,
+                PivotStrategy::Last => len - 1,
+                PivotStrategy::Middle => len / 2,
+                PivotStrategy::Random(_) => self.rng.below(len as u64) as usize,
+                PivotStrategy::MedianOfThree => {
+                    median_of_three(sequence, [0, len / 2, len - 1], compare)
+                }
+                // the triples around the quartiles must not go out of bounds
+                PivotStrategy::Ninther if len >= 8 => {
+                    ninther(sequence, [len / 4, len / 2, 3 * len / 4], compare)
+                }
+                PivotStrategy::Ninther => median_of_three(sequence, [0, len / 2, len - 1], compare),
+                PivotStrategy::MedianOfMedians => median_of_medians(sequence, compare),
+            }
+        }
+    }
+
+    /// Index of the median of the medians of groups of 5 elements, in `O(n)`.
+    ///
+    /// Every group is sorted and its median moved to the front, where the median of the medians is selected
+    /// recursively. The sequence must not be empty.
+    pub(crate) fn median_of_medians<I, F: FnMut(&I, &I) -> Ordering>(
+        sequence: &mut [I],
+        compare: &mut F,
+    ) -> usize {
+        let len = sequence.len();
+
+        if len <= 5 {
+            insertion_sort_by(sequence, &mut *compare);
+            return len / 2;
+        }
+
+        let groups = len.div_ceil(5);
 
-    /// Naive pivot selection (the middle one). Better methods exist to reduce
-    /// the chance of choosing pivots that lead to a worst-case scenario.
-    fn select_pivot<I: Ord + Copy>(sequence: &[I]) -> usize {
-        sequence.len() / 2
+        // the medians go before their groups, in places already visited
+        for group in 0..groups {
+            let start = 5 * group;
+            let end = (start + 5).min(len);
+
+            insertion_sort_by(&mut sequence[start..end], &mut *compare);
+            sequence.swap(group, start + (end - start) / 2);
+        }
+
+        let pivots = &mut PivotSelector::new(PivotStrategy::MedianOfMedians);
+
+        select_with(&mut sequence[..groups], groups / 2, pivots, compare)
     }
 
-    /// Recursive quick sort.
+    /// Recursive quick sort with the middle element as pivot.
     /// Average time required is `O(n log n)` and worst case is `O(n^2)`.
-    pub fn quick_sort<I: Ord + Copy + std::fmt::Debug>(sequence: &mut [I]) {
+    pub fn quick_sort<I: Ord>(sequence: &mut [I]) {
+        quick_sort_with_pivot(sequence, PivotStrategy::default());
+    }
+
+    /// Sorts with the comparator function `compare`, like `slice::sort_by`.
+    pub fn quick_sort_by<I, F: FnMut(&I, &I) -> Ordering>(sequence: &mut [I], compare: F) {
+        quick_sort_with_pivot_by(sequence, PivotStrategy::default(), compare);
+    }
+
+    /// Sorts by the key extracted with `key`, like `slice::sort_by_key`.
+    pub fn quick_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(sequence: &mut [I], mut key: F) {
+        quick_sort_with_pivot_by(sequence, PivotStrategy::default(), |a: &I, b: &I| {
+            key(a).cmp(&key(b))
+        });
+    }
+
+    /// Quick sort choosing the pivots with `strategy`.
+    pub fn quick_sort_with_pivot<I: Ord>(sequence: &mut [I], strategy: PivotStrategy) {
+        quick_sort_with_pivot_by(sequence, strategy, I::cmp);
+    }
+
+    /// Quick sort choosing the pivots with `strategy`, with the comparator function `compare`.
+    pub fn quick_sort_with_pivot_by<I, F: FnMut(&I, &I) -> Ordering>(
+        sequence: &mut [I],
+        strategy: PivotStrategy,
+        mut compare: F,
+    ) {
+        quick_sort_with(sequence, &mut PivotSelector::new(strategy), &mut compare);
+    }
+
+    /// The recursion goes into the shorter half and loops on the longer one, so even in the worst case the
+    /// stack takes `O(log n)` space.
+    fn quick_sort_with<I, F: FnMut(&I, &I) -> Ordering>(
+        mut sequence: &mut [I],
+        pivots: &mut PivotSelector,
+        compare: &mut F,
+    ) {
         // If len is 0 or 1 then it's already sorted
-        if sequence.len() > 1 {
+        while sequence.len() > 1 {
             // skip if a 2-sequence is already sorted
-            if sequence.len() == 2 && sequence[0] <= sequence[1] {
+            if sequence.len() == 2 && compare(&sequence[0], &sequence[1]) != Ordering::Greater {
                 return;
             }
 
-            let pivot = select_pivot(sequence);
+            let pivot = pivots.select(sequence, compare);
 
-            let second = separate(sequence, sequence[pivot]);
+            let pivot = separate(sequence, pivot, compare);
 
-            quick_sort(&mut sequence[..second]);
-            quick_sort(&mut sequence[second..]);
+            let (first, rest) = sequence.split_at_mut(pivot);
+            let second = &mut rest[1..];
+
+            if first.len() < second.len() {
+                quick_sort_with(first, pivots, compare);
+                sequence = second;
+            } else {
+                quick_sort_with(second, pivots, compare);
+                sequence = first;
+            }
         }
     }
 
-    /// splits the sequence in two halves based on the pivot value. Then returns the starting index of the second
-    /// half of the list.
-    fn separate<I: Ord + Copy + std::fmt::Debug>(sequence: &mut [I], pivot_val: I) -> usize {
-        let (mut s, mut e) = (0, sequence.len() - 1);
-
-        while s < e {
-            let s_val = sequence[s];
-            let e_val = sequence[e];
+    /// Splits the sequence in two halves around the element at `pivot`: the elements before it are not greater
+    /// and the ones after it are not smaller. Then returns the final index of the pivot.
+    ///
+    /// The pivot is moved to the front and the rest is scanned from both ends, swapping the pairs on the wrong
+    /// sides. Both scans stop on elements equal to the pivot, so these are spread evenly between the halves.
+    pub(crate) fn separate<I, F: FnMut(&I, &I) -> Ordering>(
+        sequence: &mut [I],
+        pivot: usize,
+        compare: &mut F,
+    ) -> usize {
+        sequence.swap(0, pivot);
+
+        let (pivot_val, rest) = sequence.split_first_mut().unwrap();
+        let (mut s, mut e) = (0, rest.len());
+
+        // rest[..s] <= pivot_val <= rest[e..]
+        loop {
+            while s < e && compare(&rest[s], pivot_val) == Ordering::Less {
+                s += 1;
+            }
 
-            if s_val >= pivot_val {
-                if e_val <= pivot_val {
-                    sequence.swap(e, s);
-                    s = s + 1;
-                }
-                e = e - 1;
-            } else {
-                // swap needed
-                if e_val >= pivot_val {
-                    e = e - 1;
-                }
+            while s < e && compare(&rest[e - 1], pivot_val) == Ordering::Greater {
+                e -= 1;
+            }
 
-                s = s + 1;
+            if s + 1 >= e {
+                // a single element left is equal to the pivot
+                s = e;
+                break;
             }
+
+            rest.swap(s, e - 1);
+            s += 1;
+            e -= 1;
         }
 
-        let idx = s.max(e);
+        // the last element not greater than the pivot goes to the front
+        sequence.swap(0, s);
 
-        if sequence[idx] <= pivot_val {
-            idx + 1
-        } else {
-            idx
-        }
+        s
     }
 
     #[cfg(test)]
     mod tests {
+        use super::PivotStrategy;
+        use crate::random::Rng;
+        use crate::sorting_algorithms::tests::{check_by, check_strings};
+
         #[test]
         fn simple() {
             let mut seq = [9, 8, 4, 5, 5, 6];
@@ -249,11 +651,116 @@ pub mod quick_sort {
 
             assert_eq!(&expected, &seq);
         }
+
+        #[test]
+        fn heap_data() {
+            check_strings(super::quick_sort);
+        }
+
+        fn strategies() -> Vec<PivotStrategy> {
+            vec![
+                PivotStrategy::First,
+                PivotStrategy::Last,
+                PivotStrategy::Middle,
+                PivotStrategy::Random(42),
+                PivotStrategy::MedianOfThree,
+                PivotStrategy::Ninther,
+                PivotStrategy::MedianOfMedians,
+            ]
+        }
+
+        #[test]
+        fn pivot_strategies() {
+            let mut rng = Rng::new(46);
+            let inputs: Vec<Vec<u32>> = vec![
+                (0..1000).map(|_| rng.below(1009) as u32).collect(),
+                (0..1000).map(|_| rng.below(7) as u32).collect(),
+                (0..1000).collect(),
+                (0..1000).rev().collect(),
+                vec![3; 100],
+            ];
+
+            for strategy in strategies() {
+                for input in &inputs {
+                    for &size in &[0, 1, 2, 3, 5, 8, 13, 100, input.len()] {
+                        let mut sequence = input[..size.min(input.len())].to_vec();
+                        let mut expected = sequence.clone();
+                        expected.sort_unstable();
+
+                        super::quick_sort_with_pivot(&mut sequence, strategy);
+
+                        assert_eq!(expected, sequence, "{:?}", strategy);
+                    }
+                }
+            }
+        }
+
+        /// Number of comparisons to sort `sequence`.
+        fn comparisons(mut sequence: Vec<u32>, strategy: PivotStrategy) -> usize {
+            let mut comparisons = 0;
+
+            super::quick_sort_with_pivot_by(&mut sequence, strategy, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+
+            comparisons
+        }
+
+        #[test]
+        fn textbook_cases() {
+            let n = 2000;
+            let sorted: Vec<u32> = (0..n as u32).collect();
+
+            // about n^2 / 2 comparisons in the worst case, n log n in the best case
+            assert!(comparisons(sorted.clone(), PivotStrategy::First) > n * n / 4);
+            assert!(comparisons(sorted.clone(), PivotStrategy::Last) > n * n / 4);
+
+            // a balanced split at every level takes about n log2(n) comparisons, allow a constant of 4
+            let log2 = (n as f64).log2().ceil() as usize;
+
+            for strategy in strategies().into_iter().skip(2) {
+                assert!(
+                    comparisons(sorted.clone(), strategy) < 4 * n * log2,
+                    "{:?}",
+                    strategy
+                );
+            }
+
+            // the same seed gives the same pivots
+            let mut shuffled = sorted;
+            Rng::new(46).shuffle(&mut shuffled);
+            assert_eq!(
+                comparisons(shuffled.clone(), PivotStrategy::Random(7)),
+                comparisons(shuffled, PivotStrategy::Random(7))
+            );
+        }
+
+        #[test]
+        fn median_of_medians() {
+            let mut sequence: Vec<u32> = (0..1000).collect();
+            Rng::new(46).shuffle(&mut sequence);
+            let pivot = super::median_of_medians(&mut sequence, &mut u32::cmp);
+
+            // at least 30% of the elements on each side
+            let smaller = sequence.iter().filter(|&&v| v < sequence[pivot]).count();
+            assert!((300..=700).contains(&smaller));
+        }
+
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::quick_sort_by(seq, compare),
+                |seq, key| super::quick_sort_by_key(seq, key),
+            );
+        }
     }
 }
 
 pub mod merge_sort {
-    use std::cmp::Ord;
+    use std::cmp::{Ord, Ordering};
+    use std::mem::MaybeUninit;
+    use std::ptr;
 
     /// This implementation of merge sort is recursive. The sequence is split in half and the function is called recursively
     /// on both halves. Then the two sorted halves are merged into a longer sorted sequence.
@@ -263,19 +770,40 @@ pub mod merge_sort {
     /// Thus it performs `n * O(log n)` comparisons
     /// and thus its running time is `O(n log n)`.
     ///
-    /// It allocates a temp vector of space `O(n)`.
-    pub fn merge_sort<I: Ord + Copy + Default + std::fmt::Debug>(sequence: &mut [I]) {
+    /// It allocates an uninitialised buffer of space `O(n / 2)`.
+    pub fn merge_sort<I: Ord>(sequence: &mut [I]) {
+        merge_sort_with(sequence, &mut I::cmp);
+    }
+
+    /// Sorts with the comparator function `compare`, like `slice::sort_by`.
+    pub fn merge_sort_by<I, F: FnMut(&I, &I) -> Ordering>(sequence: &mut [I], mut compare: F) {
+        merge_sort_with(sequence, &mut compare);
+    }
+
+    /// Sorts by the key extracted with `key`, like `slice::sort_by_key`.
+    pub fn merge_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(sequence: &mut [I], mut key: F) {
+        merge_sort_with(sequence, &mut |a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    fn merge_sort_with<I, F: FnMut(&I, &I) -> Ordering>(sequence: &mut [I], compare: &mut F) {
         if sequence.len() < 2 {
             // already sorted
             return;
         }
 
-        let mut temp = vec![I::default(); sequence.len()];
+        let mut buffer = Vec::with_capacity(sequence.len() / 2);
 
-        sort(sequence, temp.as_mut_slice());
+        sort(sequence, buffer.spare_capacity_mut(), compare);
     }
 
-    pub fn sort<I: Ord + Copy + std::fmt::Debug>(seq: &mut [I], temp: &mut [I]) {
+    /// Sorts `seq` with the comparator `compare`, using `buffer` as the space for merging.
+    ///
+    /// Panics if `buffer` is shorter than half of `seq`.
+    pub fn sort<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        buffer: &mut [MaybeUninit<I>],
+        compare: &mut F,
+    ) {
         if seq.len() < 2 {
             return;
         }
@@ -283,52 +811,91 @@ pub mod merge_sort {
         // split in two
         let second = seq.len() / 2;
 
-        sort(&mut seq[0..second], &mut temp[0..second]);
-        sort(&mut seq[second..], &mut temp[second..]);
-        merge(seq, second, temp);
+        sort(&mut seq[0..second], buffer, compare);
+        sort(&mut seq[second..], buffer, compare);
+        merge(seq, second, buffer, compare);
     }
 
     /// Merges the given two sorted sequences. The first sequence starts at `seq[0]`,
     /// the second at `seq[second]`.
-    pub fn merge<I: Ord + Copy + std::fmt::Debug>(seq: &mut [I], second: usize, temp: &mut [I]) {
-        let (mut i, mut j, mut k) = (0, second, 0);
-
-        // Merge at least half of the elements (the min ones)
-        while i < second && j < seq.len() {
-            if seq[i] <= seq[j] {
-                temp[k] = seq[i];
-                i += 1;
-            } else {
-                temp[k] = seq[j];
-                j += 1;
+    ///
+    /// The first sequence is moved to `buffer`, then the smaller of the two fronts is repeatedly moved to the
+    /// front of the gap in `seq`, which never overtakes the rest of the second sequence. On ties the first
+    /// sequence goes first, so the merge is stable.
+    ///
+    /// Panics if `buffer` is shorter than `second`.
+    pub fn merge<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        second: usize,
+        buffer: &mut [MaybeUninit<I>],
+        compare: &mut F,
+    ) {
+        assert!(buffer.len() >= second, "the buffer is too short");
+
+        let len = seq.len();
+        let seq = seq.as_mut_ptr();
+        let buffer = buffer.as_mut_ptr() as *mut I;
+
+        // SAFETY: `buffer` has room for the first sequence, which is moved there, leaving a gap at the front of
+        // `seq`. Every iteration moves one element into the gap and shifts it by one. If a comparison panics, the
+        // gap drops the remaining elements of the first sequence into place, so every element is in `seq` once.
+        unsafe {
+            ptr::copy_nonoverlapping(seq, buffer, second);
+
+            let mut gap = Gap {
+                start: buffer,
+                end: buffer.add(second),
+                dest: seq,
+            };
+
+            let mut right = seq.add(second);
+            let right_end = seq.add(len);
+
+            while gap.start < gap.end && right < right_end {
+                let src = if compare(&*right, &*gap.start) == Ordering::Less {
+                    let src = right;
+                    right = right.add(1);
+                    src
+                } else {
+                    let src = gap.start;
+                    gap.start = gap.start.add(1);
+                    src
+                };
+
+                ptr::copy_nonoverlapping(src, gap.dest, 1);
+                gap.dest = gap.dest.add(1);
             }
 
-            k += 1;
+            // the rest of the second sequence is already in place, dropping the gap moves the first one
         }
+    }
 
-        // Now add the remaining ones to temp (they're all bigger
-        // than the previous ones)
-
-        while k < temp.len() && i < second {
-            temp[k] = seq[i];
-            i += 1;
-            k += 1;
-        }
+    /// The elements of the buffer in `start..end` not yet merged, and the front of the gap they fill in the sequence.
+    pub(super) struct Gap<I> {
+        pub(super) start: *mut I,
+        pub(super) end: *mut I,
+        pub(super) dest: *mut I,
+    }
 
-        while k < temp.len() && j < seq.len() {
-            temp[k] = seq[j];
-            j += 1;
-            k += 1;
+    impl<I> Drop for Gap<I> {
+        fn drop(&mut self) {
+            // SAFETY: the gap in the sequence is as long as the remaining elements of the buffer.
+            unsafe {
+                let len = self.end.offset_from(self.start) as usize;
+                ptr::copy_nonoverlapping(self.start, self.dest, len);
+            }
         }
-
-        // Copy the values from temp back to the original array.
-        seq.iter_mut()
-            .enumerate()
-            .for_each(|(idx, el)| *el = temp[idx]);
     }
 
     #[cfg(test)]
     mod tests {
+        use std::cell::Cell;
+        use std::cmp::Ordering;
+        use std::panic::{self, AssertUnwindSafe};
+        use std::rc::Rc;
+
+        use crate::sorting_algorithms::tests::{check_by, check_strings, Entry};
+
         #[test]
         fn correct() {
             let sequence = &mut [9, 8, 2, 3, 1, 5, 6, 4, 7];
@@ -338,5 +905,2008 @@ pub mod merge_sort {
 
             assert_eq!(sequence, expected);
         }
+
+        #[test]
+        fn heap_data() {
+            check_strings(super::merge_sort);
+        }
+
+        #[test]
+        fn stable() {
+            let mut sequence: Vec<Entry> = (0..100).map(|i| Entry((i * 37) % 10, i)).collect();
+            let mut expected = sequence.clone();
+            expected.sort();
+
+            super::merge_sort(&mut sequence);
+
+            assert!(expected
+                .iter()
+                .zip(sequence.iter())
+                .all(|(a, b)| a.1 == b.1));
+        }
+
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::merge_sort_by(seq, compare),
+                |seq, key| super::merge_sort_by_key(seq, key),
+            );
+        }
+
+        /// Panics on the comparison number `limit`.
+        struct Fragile<'a> {
+            value: Rc<u32>,
+            comparisons: &'a Cell<usize>,
+            limit: usize,
+        }
+
+        impl PartialEq for Fragile<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.cmp(other) == Ordering::Equal
+            }
+        }
+
+        impl Eq for Fragile<'_> {}
+
+        impl PartialOrd for Fragile<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Fragile<'_> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.comparisons.set(self.comparisons.get() + 1);
+                assert!(self.comparisons.get() < self.limit, "comparison limit");
+                self.value.cmp(&other.value)
+            }
+        }
+
+        #[test]
+        fn panic_safe() {
+            let value = Rc::new(0);
+            let comparisons = Cell::new(0);
+
+            let mut sequence: Vec<Fragile> = (0..50)
+                .map(|i| Fragile {
+                    value: Rc::new((i * 37) % 50),
+                    comparisons: &comparisons,
+                    limit: 150,
+                })
+                .chain((0..10).map(|_| Fragile {
+                    value: value.clone(),
+                    comparisons: &comparisons,
+                    limit: 150,
+                }))
+                .collect();
+
+            let result = panic::catch_unwind(AssertUnwindSafe(|| super::merge_sort(&mut sequence)));
+            assert!(result.is_err());
+
+            // every element is still there once
+            let mut values: Vec<u32> = sequence.iter().map(|f| *f.value).collect();
+            values.sort_unstable();
+            assert_eq!(11, Rc::strong_count(&value));
+            assert_eq!(60, values.len());
+            assert!((0..50).all(|v| values.binary_search(&v).is_ok()));
+        }
+    }
+}
+
+pub mod intro_sort {
+    use std::cmp::{Ord, Ordering};
+
+    use super::heap_sort::heap_sort_by;
+    use super::insertion_sort::insertion_sort_by;
+
+    /// Slices up to this length are sorted with insertion sort.
+    const INSERTION_THRESHOLD: usize = 20;
+    /// Slices from this length take the ninther as pivot, shorter ones the median of three.
+    const NINTHER_THRESHOLD: usize = 128;
+
+    /// Introspective sort, an unstable sort based on quick sort that is `O(n log n)` in the worst case.
+    ///
+    /// - The pivot is the median of three elements spread across the slice, or for long slices the ninther (the
+    ///   median of the medians of three triples), so sorted, reversed and most structured inputs split evenly.
+    /// - The partition is three-way: the elements equal to the pivot are grouped in the middle and never visited
+    ///   again, so sequences with many duplicates take `O(n log d)` time with `d` distinct values.
+    /// - Short slices, where quick sort's overhead dominates, are sorted with insertion sort.
+    /// - The recursion depth is limited to about `2 log n`. Past that the pivots have been bad too many times,
+    ///   and the slice is sorted with heap sort.
+    ///
+    /// The recursion goes into the smaller half and loops on the larger one, so the stack takes `O(log n)` space.
+    pub fn intro_sort<I: Ord>(seq: &mut [I]) {
+        intro_sort_with(seq, &mut I::cmp);
+    }
+
+    /// Sorts with the comparator function `compare`, like `slice::sort_unstable_by`.
+    pub fn intro_sort_by<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], mut compare: F) {
+        intro_sort_with(seq, &mut compare);
+    }
+
+    /// Sorts by the key extracted with `key`, like `slice::sort_unstable_by_key`.
+    pub fn intro_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(seq: &mut [I], mut key: F) {
+        intro_sort_with(seq, &mut |a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    fn intro_sort_with<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], compare: &mut F) {
+        // floor(log2(len)), 0 for an empty slice
+        let log = (usize::BITS - seq.len().leading_zeros()).saturating_sub(1);
+
+        recurse(seq, compare, 2 * log as usize);
+    }
+
+    fn recurse<I, F: FnMut(&I, &I) -> Ordering>(
+        mut seq: &mut [I],
+        compare: &mut F,
+        mut limit: usize,
+    ) {
+        loop {
+            if seq.len() <= INSERTION_THRESHOLD {
+                insertion_sort_by(seq, &mut *compare);
+                return;
+            }
+
+            if limit == 0 {
+                heap_sort_by(seq, &mut *compare);
+                return;
+            }
+
+            limit -= 1;
+
+            let pivot = choose_pivot(seq, compare);
+            let (lt, gt) = partition3(seq, pivot, compare);
+
+            let (left, rest) = seq.split_at_mut(lt);
+            let right = &mut rest[gt - lt..];
+
+            if left.len() < right.len() {
+                recurse(left, compare, limit);
+                seq = right;
+            } else {
+                recurse(right, compare, limit);
+                seq = left;
+            }
+        }
+    }
+
+    fn choose_pivot<I, F: FnMut(&I, &I) -> Ordering>(seq: &[I], compare: &mut F) -> usize {
+        let len = seq.len();
+        let (a, b, c) = (len / 4, len / 2, 3 * len / 4);
+
+        if len < NINTHER_THRESHOLD {
+            median_of_three(seq, [a, b, c], compare)
+        } else {
+            ninther(seq, [a, b, c], compare)
+        }
+    }
+
+    /// Index of the median of the elements at the three indices.
+    pub(crate) fn median_of_three<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &[I],
+        [a, b, c]: [usize; 3],
+        compare: &mut F,
+    ) -> usize {
+        let less =
+            |x: usize, y: usize, compare: &mut F| compare(&seq[x], &seq[y]) == Ordering::Less;
+
+        let (lo, hi) = if less(b, a, compare) { (b, a) } else { (a, b) };
+
+        if less(c, lo, compare) {
+            lo
+        } else if less(hi, c, compare) {
+            hi
+        } else {
+            c
+        }
+    }
+
+    /// Index of the median of the medians of three elements around each of the three indices, which must not be
+    /// at either end of the slice.
+    pub(crate) fn ninther<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &[I],
+        [a, b, c]: [usize; 3],
+        compare: &mut F,
+    ) -> usize {
+        let mut around = |mid: usize| median_of_three(seq, [mid - 1, mid, mid + 1], compare);
+        let medians = [around(a), around(b), around(c)];
+
+        median_of_three(seq, medians, compare)
+    }
+
+    /// Three-way partition around the element at `pivot`: returns `(lt, gt)` such that the elements in `..lt` are
+    /// smaller than the pivot, the ones in `lt..gt` are equal and the ones in `gt..` are greater.
+    ///
+    /// Dijkstra's Dutch national flag scheme: the equal elements are kept in `lt..i` behind the scanning index `i`,
+    /// which starts right after the pivot moved to the front, the smaller ones before them and the greater ones
+    /// at the end.
+    pub(crate) fn partition3<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        pivot: usize,
+        compare: &mut F,
+    ) -> (usize, usize) {
+        seq.swap(0, pivot);
+
+        let (mut lt, mut i, mut gt) = (0, 1, seq.len());
+
+        // `seq[lt]` is always equal to the pivot
+        while i < gt {
+            match compare(&seq[i], &seq[lt]) {
+                Ordering::Less => {
+                    seq.swap(lt, i);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Greater => {
+                    gt -= 1;
+                    seq.swap(i, gt);
+                }
+                Ordering::Equal => i += 1,
+            }
+        }
+
+        (lt, gt)
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::cell::Cell;
+
+        use crate::random::Rng;
+        use crate::sorting_algorithms::tests::{check_by, check_strings};
+
+        /// Pseudo-random values below `modulo`.
+        fn shuffled(len: usize, modulo: u64) -> Vec<u64> {
+            let mut rng = Rng::new(45);
+            (0..len).map(|_| rng.below(modulo)).collect()
+        }
+
+        #[test]
+        fn correct() {
+            let len = 5000;
+            let inputs = vec![
+                shuffled(len, u64::MAX),
+                shuffled(len, 3),
+                (0..len as u64).collect(),
+                (0..len as u64).rev().collect(),
+                // organ pipe
+                (0..len as u64).map(|i| i.min(len as u64 - i)).collect(),
+                vec![7; len],
+            ];
+
+            for input in inputs {
+                for &size in &[0, 1, 2, 20, 21, 127, 128, 1000, len] {
+                    let mut sequence = input[..size].to_vec();
+                    let mut expected = sequence.clone();
+                    expected.sort_unstable();
+
+                    super::intro_sort(&mut sequence);
+
+                    assert_eq!(expected, sequence);
+                }
+            }
+        }
+
+        #[test]
+        fn heap_sort_fallback() {
+            let mut sequence = shuffled(1000, 100);
+            let mut expected = sequence.clone();
+            expected.sort_unstable();
+
+            super::recurse(&mut sequence, &mut u64::cmp, 0);
+
+            assert_eq!(expected, sequence);
+        }
+
+        #[test]
+        fn duplicates_linear() {
+            let comparisons = Cell::new(0);
+            let mut sequence = shuffled(10_000, 4);
+
+            super::intro_sort_by(&mut sequence, |a, b| {
+                comparisons.set(comparisons.get() + 1);
+                a.cmp(b)
+            });
+
+            // every partition removes a value, about one pass for each
+            assert!(sequence.windows(2).all(|w| w[0] <= w[1]));
+            assert!(comparisons.get() < 10 * 10_000);
+        }
+
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::intro_sort_by(seq, compare),
+                |seq, key| super::intro_sort_by_key(seq, key),
+            );
+        }
+
+        #[test]
+        fn heap_data() {
+            check_strings(super::intro_sort);
+        }
+    }
+}
+
+pub mod selection {
+    use std::cmp::{Ord, Ordering};
+
+    use super::intro_sort::intro_sort_by;
+    use super::quick_sort::{separate, PivotSelector, PivotStrategy};
+
+    /// Seed of the random pivots of `select_nth`.
+    const SEED: u64 = 0x5e1e_c7ed;
+
+    /// Quickselect: rearranges the sequence so that the element at `nth` is the one that would be there if it was
+    /// sorted, with no greater elements before it and no smaller elements after it, then returns it.
+    ///
+    /// Like quick sort, the sequence is split around a pivot with `quick_sort::separate`, but only the half
+    /// containing `nth` is visited. With random pivots the halves shrink geometrically on average, so the expected
+    /// time is `O(n)` and the worst case `O(n^2)`.
+    ///
+    /// Panics if `nth` is out of bounds.
+    pub fn select_nth<I: Ord>(seq: &mut [I], nth: usize) -> &I {
+        select_nth_by(seq, nth, I::cmp)
+    }
+
+    /// Same as `select_nth`, with the comparator function `compare`.
+    pub fn select_nth_by<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        nth: usize,
+        mut compare: F,
+    ) -> &I {
+        let pivots = &mut PivotSelector::new(PivotStrategy::Random(SEED));
+
+        &seq[select_with(seq, nth, pivots, &mut compare)]
+    }
+
+    /// Same as `select_nth`, with the median of medians as pivot: at least 30% of the elements are discarded at
+    /// every step, so the worst case is `O(n)`, with a higher constant than quickselect's average.
+    ///
+    /// Panics if `nth` is out of bounds.
+    pub fn select_nth_deterministic<I: Ord>(seq: &mut [I], nth: usize) -> &I {
+        select_nth_deterministic_by(seq, nth, I::cmp)
+    }
+
+    /// Same as `select_nth_deterministic`, with the comparator function `compare`.
+    pub fn select_nth_deterministic_by<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        nth: usize,
+        mut compare: F,
+    ) -> &I {
+        let pivots = &mut PivotSelector::new(PivotStrategy::MedianOfMedians);
+
+        &seq[select_with(seq, nth, pivots, &mut compare)]
+    }
+
+    /// Quickselect with the pivots chosen by `pivots`, returns `nth`.
+    pub(crate) fn select_with<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        nth: usize,
+        pivots: &mut PivotSelector,
+        compare: &mut F,
+    ) -> usize {
+        assert!(nth < seq.len(), "index out of bounds");
+
+        let (mut start, mut end) = (0, seq.len());
+
+        loop {
+            let part = &mut seq[start..end];
+            let pivot = pivots.select(part, compare);
+            let pivot = start + separate(part, pivot, compare);
+
+            match nth.cmp(&pivot) {
+                Ordering::Less => end = pivot,
+                Ordering::Greater => start = pivot + 1,
+                Ordering::Equal => return nth,
+            }
+        }
+    }
+
+    /// Partial sort: moves the `k` greatest elements to the front in descending order and returns them, leaving
+    /// the rest in no particular order. Takes `O(n + k log k)` expected time.
+    ///
+    /// `k` is capped to the length of the sequence.
+    pub fn top_k<I: Ord>(seq: &mut [I], k: usize) -> &mut [I] {
+        top_k_by(seq, k, I::cmp)
+    }
+
+    /// Same as `top_k`, with the comparator function `compare`: `|a, b| b.cmp(a)` gives the `k` smallest elements
+    /// in ascending order.
+    pub fn top_k_by<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        k: usize,
+        mut compare: F,
+    ) -> &mut [I] {
+        let k = k.min(seq.len());
+        let mut descending = |a: &I, b: &I| compare(b, a);
+
+        if k > 0 && k < seq.len() {
+            let pivots = &mut PivotSelector::new(PivotStrategy::Random(SEED));
+            select_with(seq, k - 1, pivots, &mut descending);
+        }
+
+        let top = &mut seq[..k];
+        intro_sort_by(top, descending);
+        top
+    }
+
+    /// The median, or the lower one for sequences of even length, or `None` if the sequence is empty.
+    pub fn median<I: Ord>(seq: &mut [I]) -> Option<&I> {
+        if seq.is_empty() {
+            None
+        } else {
+            let nth = (seq.len() - 1) / 2;
+            Some(select_nth(seq, nth))
+        }
+    }
+
+    /// The `p`-th percentile with the nearest-rank method: the smallest element not smaller than `p`% of the
+    /// elements. `None` if the sequence is empty.
+    ///
+    /// Panics if `p` is not in `0.0..=100.0`.
+    pub fn percentile<I: Ord>(seq: &mut [I], p: f64) -> Option<&I> {
+        percentile_by(seq, p, I::cmp)
+    }
+
+    /// Same as `percentile`, with the comparator function `compare`.
+    pub fn percentile_by<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        p: f64,
+        compare: F,
+    ) -> Option<&I> {
+        assert!(
+            (0.0..=100.0).contains(&p),
+            "the percentile must be in 0..=100"
+        );
+
+        if seq.is_empty() {
+            return None;
+        }
+
+        // the rank counts from 1, the 0th percentile is the minimum
+        let rank = (p / 100.0 * seq.len() as f64).ceil() as usize;
+        let nth = rank.max(1) - 1;
+
+        Some(select_nth_by(seq, nth, compare))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::random::Rng;
+
+        /// Values with duplicates.
+        fn values() -> Vec<u32> {
+            let mut rng = Rng::new(47);
+            (0..1000).map(|_| rng.below(251) as u32).collect()
+        }
+
+        #[test]
+        fn select() {
+            let values = values();
+            let mut sorted = values.clone();
+            sorted.sort_unstable();
+
+            for nth in [0, 1, 250, 499, 500, 998, 999] {
+                let mut sequence = values.clone();
+                assert_eq!(sorted[nth], *super::select_nth(&mut sequence, nth));
+                assert!(sequence[..nth].iter().all(|&v| v <= sorted[nth]));
+                assert!(sequence[nth..].iter().all(|&v| v >= sorted[nth]));
+
+                let mut sequence = values.clone();
+                assert_eq!(
+                    sorted[nth],
+                    *super::select_nth_deterministic(&mut sequence, nth)
+                );
+                assert!(sequence[..nth].iter().all(|&v| v <= sorted[nth]));
+                assert!(sequence[nth..].iter().all(|&v| v >= sorted[nth]));
+            }
+
+            let mut sequence = values.clone();
+            assert_eq!(
+                sorted[999 - 10],
+                *super::select_nth_by(&mut sequence, 10, |a, b| b.cmp(a))
+            );
+        }
+
+        #[test]
+        #[should_panic]
+        fn select_out_of_bounds() {
+            super::select_nth(&mut [1, 2, 3], 3);
+        }
+
+        #[test]
+        fn top_k() {
+            let values = values();
+            let mut sorted = values.clone();
+            sorted.sort_unstable_by(|a, b| b.cmp(a));
+
+            for k in [0, 1, 10, 999, 1000, 2000] {
+                let mut sequence = values.clone();
+                let top = super::top_k(&mut sequence, k);
+
+                assert_eq!(&sorted[..k.min(1000)], top);
+            }
+
+            let mut sequence = values;
+            let bottom = super::top_k_by(&mut sequence, 3, |a, b| b.cmp(a));
+            sorted.reverse();
+            assert_eq!(&sorted[..3], bottom);
+        }
+
+        #[test]
+        fn median_and_percentile() {
+            assert_eq!(None, super::median::<u32>(&mut []));
+            assert_eq!(Some(&2), super::median(&mut [3, 1, 2]));
+            assert_eq!(Some(&2), super::median(&mut [4, 1, 3, 2]));
+
+            let mut sequence: Vec<u32> = (1..=100).rev().collect();
+
+            assert_eq!(Some(&1), super::percentile(&mut sequence, 0.0));
+            assert_eq!(Some(&1), super::percentile(&mut sequence, 1.0));
+            assert_eq!(Some(&50), super::percentile(&mut sequence, 50.0));
+            assert_eq!(Some(&90), super::percentile(&mut sequence, 89.5));
+            assert_eq!(Some(&99), super::percentile(&mut sequence, 99.0));
+            assert_eq!(Some(&100), super::percentile(&mut sequence, 100.0));
+            assert_eq!(None, super::percentile::<u32>(&mut [], 50.0));
+        }
+    }
+}
+
+pub mod radix_sort {
+    use super::insertion_sort::insertion_sort_by;
+
+    /// Extraction of the key of the integer sorts: an unsigned integer with the same order as the values.
+    ///
+    /// Implemented by the primitive integers and floats. Records can implement it to be sorted by a field.
+    pub trait RadixKey {
+        fn radix_key(&self) -> u64;
+    }
+
+    macro_rules! impl_radix_key_unsigned {
+        ($($t:ty),*) => {
+            $(impl RadixKey for $t {
+                fn radix_key(&self) -> u64 {
+                    *self as u64
+                }
+            })*
+        };
+    }
+
+    macro_rules! impl_radix_key_signed {
+        ($($t:ty),*) => {
+            $(impl RadixKey for $t {
+                /// Two's complement with the sign bit flipped: the negative values come first.
+                fn radix_key(&self) -> u64 {
+                    (*self as i64 as u64) ^ (1 << 63)
+                }
+            })*
+        };
+    }
+
+    impl_radix_key_unsigned!(u8, u16, u32, u64, usize, bool, char);
+    impl_radix_key_signed!(i8, i16, i32, i64, isize);
+
+    impl RadixKey for f64 {
+        /// The bits of the positive values with the sign bit set, and of the negative ones all flipped, which
+        /// reverses their order. This is the order of `f64::total_cmp`.
+        fn radix_key(&self) -> u64 {
+            let bits = self.to_bits();
+
+            if bits >> 63 == 1 {
+                !bits
+            } else {
+                bits | 1 << 63
+            }
+        }
+    }
+
+    impl RadixKey for f32 {
+        fn radix_key(&self) -> u64 {
+            (*self as f64).radix_key()
+        }
+    }
+
+    /// Extraction of the key of the string sorts: a byte string compared lexicographically.
+    ///
+    /// Records can implement it to be sorted by a string field.
+    pub trait ByteKey {
+        fn byte_key(&self) -> &[u8];
+    }
+
+    impl ByteKey for str {
+        fn byte_key(&self) -> &[u8] {
+            self.as_bytes()
+        }
+    }
+
+    impl ByteKey for String {
+        fn byte_key(&self) -> &[u8] {
+            self.as_bytes()
+        }
+    }
+
+    impl ByteKey for [u8] {
+        fn byte_key(&self) -> &[u8] {
+            self
+        }
+    }
+
+    impl ByteKey for Vec<u8> {
+        fn byte_key(&self) -> &[u8] {
+            self
+        }
+    }
+
+    impl<const N: usize> ByteKey for [u8; N] {
+        fn byte_key(&self) -> &[u8] {
+            self
+        }
+    }
+
+    impl<T: ByteKey + ?Sized> ByteKey for &T {
+        fn byte_key(&self) -> &[u8] {
+            (**self).byte_key()
+        }
+    }
+
+    /// Rearranges the sequence so that the element at `order[i]` goes to `i`, in `O(n)` swaps.
+    ///
+    /// The integer sorts find the order on the keys only and move the elements once at the end, so they work
+    /// on any type. Every cycle of the permutation is followed from its start, each swap putting an element in
+    /// its place, and the visited positions are marked in `order`.
+    pub(crate) fn apply_permutation<T>(seq: &mut [T], mut order: Vec<usize>) {
+        for start in 0..seq.len() {
+            let mut cursor = start;
+
+            while order[cursor] != cursor {
+                let next = order[cursor];
+                order[cursor] = cursor;
+
+                if next == start {
+                    break;
+                }
+
+                seq.swap(cursor, next);
+                cursor = next;
+            }
+        }
+    }
+
+    /// LSD radix sort: a stable counting sort on every byte of the keys, from the least significant one.
+    ///
+    /// After the pass on byte `b`, the elements are sorted on the lowest `b + 1` bytes, since the pass is
+    /// stable. The passes where all keys have the same byte are skipped. It takes `O(n)` time for each of the
+    /// 8 bytes of the keys and `O(n)` space, stable.
+    pub fn radix_sort<T: RadixKey>(seq: &mut [T]) {
+        radix_sort_by_key(seq, |item| item.radix_key());
+    }
+
+    /// Sorts by the key extracted with `key`, which is called once per element.
+    pub fn radix_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(seq: &mut [T], mut key: F) {
+        let len = seq.len();
+
+        let mut items: Vec<(u64, usize)> = seq
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| (key(item).radix_key(), idx))
+            .collect();
+        let mut buffer = items.clone();
+
+        for shift in (0..64).step_by(8) {
+            let digit = |key: u64| (key >> shift) as u8 as usize;
+            let mut offsets = [0; 256];
+
+            for &(key, _) in &items {
+                offsets[digit(key)] += 1;
+            }
+
+            if offsets.contains(&len) {
+                continue;
+            }
+
+            // first position of every digit
+            let mut sum = 0;
+            for offset in offsets.iter_mut() {
+                sum += *offset;
+                *offset = sum - *offset;
+            }
+
+            for &(key, idx) in &items {
+                buffer[offsets[digit(key)]] = (key, idx);
+                offsets[digit(key)] += 1;
+            }
+
+            std::mem::swap(&mut items, &mut buffer);
+        }
+
+        apply_permutation(seq, items.into_iter().map(|(_, idx)| idx).collect());
+    }
+
+    /// Buckets with at most this many elements are sorted with insertion sort.
+    const MSD_INSERTION_THRESHOLD: usize = 16;
+
+    /// MSD radix sort for strings: the elements are distributed by their first byte, with the strings that end
+    /// first, then every bucket is sorted the same way by the next byte.
+    ///
+    /// Only the bytes of the distinguishing prefixes are visited, and small buckets are sorted with insertion
+    /// sort. It takes `O(n + D)` time, with `D` the total length of the distinguishing prefixes, and `O(n)`
+    /// space. It's stable.
+    pub fn msd_radix_sort<T: ByteKey>(seq: &mut [T]) {
+        msd_radix_sort_by_key(seq, |item| item.byte_key());
+    }
+
+    /// Sorts by the byte string extracted with `key`.
+    pub fn msd_radix_sort_by_key<T, F: Fn(&T) -> &[u8]>(seq: &mut [T], key: F) {
+        let keys: Vec<&[u8]> = seq.iter().map(&key).collect();
+        let mut order: Vec<usize> = (0..seq.len()).collect();
+        let mut buffer = order.clone();
+
+        msd(&keys, &mut order, &mut buffer);
+
+        apply_permutation(seq, order);
+    }
+
+    /// Sorts the indices in `order` by their keys.
+    ///
+    /// The buckets left to sort are kept on a stack with the depth of their first unsorted byte instead of
+    /// recursing, since a long common prefix would take one frame per byte. The buckets are disjoint and hold at
+    /// least two indices, so there are at most `n / 2` of them.
+    fn msd(keys: &[&[u8]], order: &mut [usize], buffer: &mut [usize]) {
+        let mut pending = vec![(0, order.len(), 0)];
+
+        while let Some((start, end, depth)) = pending.pop() {
+            let order = &mut order[start..end];
+
+            if order.len() <= MSD_INSERTION_THRESHOLD {
+                // the previous bytes are equal
+                insertion_sort_by(order, |&a, &b| {
+                    let (a, b) = (
+                        &keys[a][depth.min(keys[a].len())..],
+                        &keys[b][depth.min(keys[b].len())..],
+                    );
+                    a.cmp(b)
+                });
+                continue;
+            }
+
+            // bucket 0 for the strings that end, then one for each byte
+            let bucket = |idx: usize| keys[idx].get(depth).map_or(0, |&byte| byte as usize + 1);
+            let mut offsets = [0; 257];
+
+            for &idx in order.iter() {
+                offsets[bucket(idx)] += 1;
+            }
+
+            let mut sum = 0;
+            for offset in offsets.iter_mut() {
+                sum += *offset;
+                *offset = sum - *offset;
+            }
+
+            let starts = offsets;
+            let buffer = &mut buffer[start..end];
+
+            for &idx in order.iter() {
+                buffer[offsets[bucket(idx)]] = idx;
+                offsets[bucket(idx)] += 1;
+            }
+
+            order.copy_from_slice(buffer);
+
+            // the strings that ended are equal, the others go on with the next byte
+            for byte in 1..257 {
+                let (bucket_start, bucket_end) = (starts[byte], offsets[byte]);
+
+                if bucket_end - bucket_start > 1 {
+                    pending.push((start + bucket_start, start + bucket_end, depth + 1));
+                }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::{ByteKey, RadixKey};
+        use crate::random::Rng;
+
+        #[derive(Debug, PartialEq)]
+        struct Record {
+            id: u32,
+            score: i64,
+            name: String,
+        }
+
+        impl RadixKey for Record {
+            fn radix_key(&self) -> u64 {
+                self.score.radix_key()
+            }
+        }
+
+        impl ByteKey for Record {
+            fn byte_key(&self) -> &[u8] {
+                self.name.as_bytes()
+            }
+        }
+
+        fn records() -> Vec<Record> {
+            let mut rng = Rng::new(48);
+
+            (0..500)
+                .map(|i| Record {
+                    id: i,
+                    score: rng.below(101) as i64 - 50,
+                    name: format!("{:x}", rng.below(997)),
+                })
+                .collect()
+        }
+
+        #[test]
+        fn integers() {
+            let mut rng = Rng::new(48);
+            let mut unsigned: Vec<u64> = (0..1000).map(|_| rng.next_u64()).collect();
+            let mut expected = unsigned.clone();
+            expected.sort_unstable();
+            super::radix_sort(&mut unsigned);
+            assert_eq!(expected, unsigned);
+
+            let mut signed: Vec<i32> = (0..1000).map(|_| rng.below(2001) as i32 - 1000).collect();
+            signed.extend([i32::MIN, i32::MAX, 0, -1]);
+            let mut expected = signed.clone();
+            expected.sort_unstable();
+            super::radix_sort(&mut signed);
+            assert_eq!(expected, signed);
+
+            let mut small: Vec<u8> = (0..300).map(|i| (i * 37 % 256) as u8).collect();
+            let mut expected = small.clone();
+            expected.sort_unstable();
+            super::radix_sort(&mut small);
+            assert_eq!(expected, small);
+        }
+
+        #[test]
+        fn floats() {
+            let mut floats = vec![
+                3.5,
+                -0.0,
+                0.0,
+                f64::INFINITY,
+                -1e300,
+                1e-300,
+                f64::NEG_INFINITY,
+                -2.25,
+                f64::MIN_POSITIVE,
+                42.0,
+            ];
+            let mut expected = floats.clone();
+            expected.sort_by(f64::total_cmp);
+
+            super::radix_sort(&mut floats);
+
+            assert!(expected
+                .iter()
+                .zip(&floats)
+                .all(|(a, b)| a.to_bits() == b.to_bits()));
+        }
+
+        #[test]
+        fn records_stable() {
+            let mut by_score = records();
+            let mut expected = records();
+            expected.sort_by_key(|record| record.score);
+
+            super::radix_sort(&mut by_score);
+            assert_eq!(expected, by_score);
+
+            super::radix_sort_by_key(&mut by_score, |record| record.id);
+            assert_eq!(records(), by_score);
+        }
+
+        #[test]
+        fn strings() {
+            let mut by_name = records();
+            let mut expected = records();
+            expected.sort_by(|a, b| a.name.cmp(&b.name));
+
+            super::msd_radix_sort(&mut by_name);
+            assert_eq!(expected, by_name);
+
+            let mut words = vec![
+                "banana",
+                "",
+                "apple",
+                "app",
+                "b",
+                "apple",
+                "applesauce",
+                "ba",
+            ];
+            let mut expected = words.clone();
+            expected.sort_unstable();
+
+            super::msd_radix_sort(&mut words);
+            assert_eq!(expected, words);
+
+            let mut descending: Vec<Vec<u8>> = (0..100u8).map(|i| vec![i % 7, i % 3, i]).collect();
+            let mut expected = descending.clone();
+            expected.sort_unstable();
+
+            super::msd_radix_sort_by_key(&mut descending, |bytes| bytes);
+            assert_eq!(expected, descending);
+        }
+
+        #[test]
+        fn long_common_prefix() {
+            // one bucket per byte of the prefix, more than the insertion sort threshold
+            let prefix = "x".repeat(20_000);
+            let mut strings: Vec<String> = (0..40)
+                .map(|i| format!("{}{}", prefix, (i * 7) % 40))
+                .collect();
+            strings.push(prefix.clone());
+            let mut expected = strings.clone();
+            expected.sort_unstable();
+
+            super::msd_radix_sort_by_key(&mut strings, |string| string.as_bytes());
+            assert_eq!(expected, strings);
+        }
+    }
+}
+
+pub mod counting_sort {
+    use std::convert::TryFrom;
+
+    use super::radix_sort::{apply_permutation, radix_sort_by_key, RadixKey};
+
+    /// Counting sort: the occurrences of every key are counted, their prefix sums give the first position of
+    /// every key, and the elements are placed there in order.
+    ///
+    /// It takes `O(n + k)` time and space, with `k` the range between the smallest and greatest keys, so it's
+    /// meant for keys spanning a small range. It's stable.
+    ///
+    /// Keys whose range doesn't fit in a `usize` are sorted with `radix_sort` instead. Panics if the counts of a
+    /// smaller range are too large to allocate.
+    pub fn counting_sort<T: RadixKey>(seq: &mut [T]) {
+        counting_sort_by_key(seq, |item| item.radix_key());
+    }
+
+    /// Sorts by the key extracted with `key`, which is called once per element.
+    pub fn counting_sort_by_key<T, K: RadixKey, F: FnMut(&T) -> K>(seq: &mut [T], mut key: F) {
+        let keys: Vec<u64> = seq.iter().map(|item| key(item).radix_key()).collect();
+
+        let (min, max) = match (keys.iter().min(), keys.iter().max()) {
+            (Some(&min), Some(&max)) => (min, max),
+            _ => return,
+        };
+
+        let range = match usize::try_from(max - min)
+            .ok()
+            .and_then(|range| range.checked_add(1))
+        {
+            Some(range) => range,
+            None => {
+                // the counts can't be indexed, e.g. keys spanning all of `u64`
+                let mut order: Vec<usize> = (0..keys.len()).collect();
+                radix_sort_by_key(&mut order, |&idx| keys[idx]);
+                apply_permutation(seq, order);
+                return;
+            }
+        };
+
+        let mut offsets = vec![0; range];
+
+        for &key in &keys {
+            offsets[(key - min) as usize] += 1;
+        }
+
+        let mut sum = 0;
+        for offset in offsets.iter_mut() {
+            sum += *offset;
+            *offset = sum - *offset;
+        }
+
+        let mut order = vec![0; keys.len()];
+
+        for (idx, &key) in keys.iter().enumerate() {
+            let offset = &mut offsets[(key - min) as usize];
+            order[*offset] = idx;
+            *offset += 1;
+        }
+
+        apply_permutation(seq, order);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        #[test]
+        fn correct() {
+            let mut sequence: Vec<i16> = (0..1000).map(|i| ((i * 37) % 201 - 100) as i16).collect();
+            let mut expected = sequence.clone();
+            expected.sort_unstable();
+
+            super::counting_sort(&mut sequence);
+
+            assert_eq!(expected, sequence);
+            super::counting_sort::<u8>(&mut []);
+        }
+
+        #[test]
+        fn full_range() {
+            let mut signed = vec![i64::MAX, 0, i64::MIN, -1, i64::MIN, 1];
+            super::counting_sort(&mut signed);
+            assert_eq!(vec![i64::MIN, i64::MIN, -1, 0, 1, i64::MAX], signed);
+
+            let mut unsigned: Vec<(u64, u32)> =
+                vec![(u64::MAX, 0), (0, 1), (u64::MAX, 2), (7, 3), (0, 4)];
+            super::counting_sort_by_key(&mut unsigned, |&(key, _)| key);
+            assert_eq!(
+                vec![(0, 1), (0, 4), (7, 3), (u64::MAX, 0), (u64::MAX, 2)],
+                unsigned
+            );
+        }
+
+        #[test]
+        fn stable() {
+            let mut sequence: Vec<(char, u32)> = "counting sort"
+                .chars()
+                .enumerate()
+                .map(|(i, c)| (c, i as u32))
+                .collect();
+            let mut expected = sequence.clone();
+            expected.sort_by_key(|&(c, _)| c);
+
+            super::counting_sort_by_key(&mut sequence, |&(c, _)| c);
+
+            assert_eq!(expected, sequence);
+        }
+    }
+}
+
+pub mod bucket_sort {
+    use super::insertion_sort::insertion_sort_by;
+    use super::radix_sort::apply_permutation;
+
+    /// Bucket sort for uniformly distributed floats: the range between the smallest and greatest key is split in
+    /// `n` buckets of the same width, the elements are distributed to the buckets, and each bucket is sorted
+    /// with insertion sort.
+    ///
+    /// With uniform keys every bucket holds O(1) elements on average, so the expected time is `O(n)`. It's
+    /// `O(n^2)` if most keys fall in the same bucket. The order is the one of `f64::total_cmp`: infinities and
+    /// NaNs go in the first or last bucket according to their sign. It takes `O(n)` space and it's stable.
+    pub fn bucket_sort(seq: &mut [f64]) {
+        bucket_sort_by_key(seq, |&value| value);
+    }
+
+    /// Sorts by the key extracted with `key`, which is called once per element.
+    pub fn bucket_sort_by_key<T, F: FnMut(&T) -> f64>(seq: &mut [T], key: F) {
+        let keys: Vec<f64> = seq.iter().map(key).collect();
+        let len = keys.len();
+
+        let finite = keys.iter().copied().filter(|key| key.is_finite());
+        let min = finite.clone().fold(f64::INFINITY, f64::min);
+        let max = finite.fold(f64::NEG_INFINITY, f64::max);
+
+        // the keys are scaled down first, `max - min` overflows if they're spread over the whole range
+        let scale = len as f64;
+        let (low, span) = (min / scale, max / scale - min / scale);
+
+        let bucket = |key: f64| {
+            if !key.is_finite() {
+                if key.is_sign_negative() {
+                    0
+                } else {
+                    len - 1
+                }
+            } else if span > 0.0 {
+                (((key / scale - low) / span * scale) as usize).min(len - 1)
+            } else {
+                0
+            }
+        };
+
+        // distribute the indices with a counting sort on the buckets
+        let mut offsets = vec![0; len + 1];
+
+        for &key in &keys {
+            offsets[bucket(key) + 1] += 1;
+        }
+
+        for idx in 1..offsets.len() {
+            offsets[idx] += offsets[idx - 1];
+        }
+
+        let starts = offsets.clone();
+        let mut order = vec![0; len];
+
+        for (idx, &key) in keys.iter().enumerate() {
+            let offset = &mut offsets[bucket(key)];
+            order[*offset] = idx;
+            *offset += 1;
+        }
+
+        for bucket in starts.windows(2) {
+            insertion_sort_by(&mut order[bucket[0]..bucket[1]], |&a, &b| {
+                keys[a].total_cmp(&keys[b])
+            });
+        }
+
+        apply_permutation(seq, order);
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use crate::random::Rng;
+
+        #[test]
+        fn uniform() {
+            let mut rng = Rng::new(48);
+            let mut sequence: Vec<f64> = (0..1000).map(|_| rng.unit() * 200.0 - 100.0).collect();
+            sequence.extend([f64::INFINITY, f64::NEG_INFINITY, -0.0, 0.0, 100.0]);
+
+            let mut expected = sequence.clone();
+            expected.sort_by(f64::total_cmp);
+
+            super::bucket_sort(&mut sequence);
+
+            assert!(expected
+                .iter()
+                .zip(&sequence)
+                .all(|(a, b)| a.to_bits() == b.to_bits()));
+        }
+
+        #[test]
+        fn records() {
+            let mut sequence: Vec<(u32, f64)> =
+                (0..100).map(|i| (i, ((i * 37) % 10) as f64)).collect();
+            let mut expected = sequence.clone();
+            expected.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            super::bucket_sort_by_key(&mut sequence, |&(_, key)| key);
+            assert_eq!(expected, sequence);
+
+            let mut equal = vec![1.5; 10];
+            super::bucket_sort(&mut equal);
+            assert_eq!(vec![1.5; 10], equal);
+
+            super::bucket_sort(&mut []);
+        }
+
+        #[test]
+        fn whole_range() {
+            let mut rng = Rng::new(48);
+            let mut sequence: Vec<f64> = (0..1000)
+                .map(|_| (rng.unit() * 2.0 - 1.0) * f64::MAX)
+                .collect();
+            sequence.extend([f64::MIN, f64::MAX, f64::MIN_POSITIVE, 0.0, -1.0]);
+
+            let mut expected = sequence.clone();
+            expected.sort_by(f64::total_cmp);
+
+            super::bucket_sort(&mut sequence);
+            assert_eq!(expected, sequence);
+
+            let mut extremes = vec![f64::MAX, f64::MIN, f64::MAX, f64::MIN];
+            super::bucket_sort(&mut extremes);
+            assert_eq!(vec![f64::MIN, f64::MIN, f64::MAX, f64::MAX], extremes);
+        }
+    }
+}
+
+pub mod tim_sort {
+    use std::cmp::{Ord, Ordering};
+    use std::mem::MaybeUninit;
+    use std::{ptr, slice};
+
+    use super::insertion_sort::insertion_sort_from;
+    use super::merge_sort::Gap;
+
+    /// Initial number of consecutive wins of a run after which a merge starts galloping.
+    const MIN_GALLOP: usize = 7;
+
+    /// TimSort, an adaptive and stable natural merge sort.
+    ///
+    /// - The sequence is split in natural runs: maximal non-descending or strictly descending sequences, the
+    ///   latter reversed in place. Runs shorter than a minimum length between 32 and 64 are extended to it
+    ///   with binary insertion sort, so that the number of runs is close to a power of two.
+    /// - The runs are pushed on a stack and merged so that, from the top, every run is shorter than the one
+    ///   below it and shorter than the sum of the two below it. The lengths grow at least like the Fibonacci
+    ///   numbers, so the stack has `O(log n)` runs, and runs of similar lengths are merged, like in merge sort.
+    /// - A merge first skips the elements of the first run smaller than the second one and the elements of the
+    ///   second run greater than the first one, then copies the shorter run to a buffer. When one run wins many
+    ///   times in a row, the merge gallops: it finds how many elements it wins with an exponential search and
+    ///   moves them at once. The threshold adapts to how often galloping pays off.
+    ///
+    /// It takes `O(n log n)` time in the worst case and `O(n)` on sorted or reversed sequences, or more
+    /// generally `O(n log r)` for `r` runs, and `O(n / 2)` space for the buffer.
+    pub fn tim_sort<I: Ord>(seq: &mut [I]) {
+        tim_sort_with(seq, &mut I::cmp);
+    }
+
+    /// Sorts with the comparator function `compare`, like `slice::sort_by`.
+    pub fn tim_sort_by<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], mut compare: F) {
+        tim_sort_with(seq, &mut compare);
+    }
+
+    /// Sorts by the key extracted with `key`, like `slice::sort_by_key`.
+    pub fn tim_sort_by_key<I, K: Ord, F: FnMut(&I) -> K>(seq: &mut [I], mut key: F) {
+        tim_sort_with(seq, &mut |a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    /// A sorted run at `start..start + len`.
+    #[derive(Debug, Clone, Copy)]
+    struct Run {
+        start: usize,
+        len: usize,
+    }
+
+    fn tim_sort_with<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], compare: &mut F) {
+        let len = seq.len();
+
+        if len < 2 {
+            return;
+        }
+
+        let min_run = min_run_length(len);
+        let mut buffer = Vec::with_capacity(len / 2);
+        let mut merger = Merger {
+            buffer: buffer.spare_capacity_mut(),
+            min_gallop: MIN_GALLOP,
+            compare,
+        };
+        let mut runs: Vec<Run> = Vec::new();
+        let mut start = 0;
+
+        while start < len {
+            let mut run = find_run(&mut seq[start..], merger.compare);
+
+            if run < min_run {
+                let forced = min_run.min(len - start);
+                insertion_sort_from(&mut seq[start..start + forced], run, merger.compare);
+                run = forced;
+            }
+
+            runs.push(Run { start, len: run });
+            start += run;
+
+            merge_collapse(seq, &mut runs, &mut merger);
+        }
+
+        // merge all the remaining runs, from the top
+        while runs.len() > 1 {
+            let mut n = runs.len() - 2;
+
+            if n > 0 && runs[n - 1].len < runs[n + 1].len {
+                n -= 1;
+            }
+
+            merge_at(seq, &mut runs, n, &mut merger);
+        }
+    }
+
+    /// Minimum length of the runs: `len` if it's shorter than 64, otherwise a number between 32 and 64 such that
+    /// `len / min_run` is a power of two or slightly less than one.
+    fn min_run_length(mut len: usize) -> usize {
+        // set if any bit shifted out is set
+        let mut rest = 0;
+
+        while len >= 64 {
+            rest |= len & 1;
+            len >>= 1;
+        }
+
+        len + rest
+    }
+
+    /// Length of the run at the start of the sequence, reversing it if it's strictly descending.
+    fn find_run<I, F: FnMut(&I, &I) -> Ordering>(seq: &mut [I], compare: &mut F) -> usize {
+        if seq.len() < 2 {
+            return seq.len();
+        }
+
+        let mut end = 2;
+
+        // strictly, so that reversing keeps the sort stable
+        if compare(&seq[1], &seq[0]) == Ordering::Less {
+            while end < seq.len() && compare(&seq[end], &seq[end - 1]) == Ordering::Less {
+                end += 1;
+            }
+
+            seq[..end].reverse();
+        } else {
+            while end < seq.len() && compare(&seq[end], &seq[end - 1]) != Ordering::Less {
+                end += 1;
+            }
+        }
+
+        end
+    }
+
+    /// Merges runs until, for the top runs `x`, `y`, `z` and `w`, from the top:
+    /// `len(y) > len(x)`, `len(z) > len(y) + len(x)` and `len(w) > len(z) + len(y)`. Checking the fourth run as
+    /// well is needed for the invariant to hold on the whole stack.
+    fn merge_collapse<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        runs: &mut Vec<Run>,
+        merger: &mut Merger<'_, I, F>,
+    ) {
+        while runs.len() > 1 {
+            let mut n = runs.len() - 2;
+
+            if (n > 0 && runs[n - 1].len <= runs[n].len + runs[n + 1].len)
+                || (n > 1 && runs[n - 2].len <= runs[n - 1].len + runs[n].len)
+            {
+                // merge the middle run with the shorter of its neighbours
+                if runs[n - 1].len < runs[n + 1].len {
+                    n -= 1;
+                }
+            } else if runs[n].len > runs[n + 1].len {
+                break;
+            }
+
+            merge_at(seq, runs, n, merger);
+        }
+    }
+
+    /// Merges the runs `n` and `n + 1` of the stack.
+    fn merge_at<I, F: FnMut(&I, &I) -> Ordering>(
+        seq: &mut [I],
+        runs: &mut Vec<Run>,
+        n: usize,
+        merger: &mut Merger<'_, I, F>,
+    ) {
+        let (first, second) = (runs[n], runs[n + 1]);
+
+        runs[n].len += second.len;
+        runs.remove(n + 1);
+
+        merger.merge(&mut seq[first.start..second.start + second.len], first.len);
+    }
+
+    /// Number of elements at the start of `seq` for which `pred` holds, given that it holds on a prefix.
+    ///
+    /// The bound is found with an exponential search, then the exact count with a binary search, so it takes
+    /// `O(log k)` comparisons for a count of `k`.
+    fn gallop<I>(seq: &[I], mut pred: impl FnMut(&I) -> bool) -> usize {
+        // `pred` holds on `..lo`
+        let (mut lo, mut step) = (0, 1);
+
+        while lo + step <= seq.len() && pred(&seq[lo + step - 1]) {
+            lo += step;
+            step *= 2;
+        }
+
+        let hi = (lo + step).min(seq.len());
+
+        lo + seq[lo..hi].partition_point(pred)
+    }
+
+    /// Number of elements at the end of `seq` for which `pred` holds, given that it holds on a suffix.
+    fn gallop_back<I>(seq: &[I], mut pred: impl FnMut(&I) -> bool) -> usize {
+        // `pred` holds on `hi..`
+        let (mut hi, mut step) = (seq.len(), 1);
+
+        while step <= hi && pred(&seq[hi - step]) {
+            hi -= step;
+            step *= 2;
+        }
+
+        let lo = hi.saturating_sub(step);
+        let failing = seq[lo..hi].partition_point(|el| !pred(el));
+
+        seq.len() - lo - failing
+    }
+
+    /// The state shared by the merges of a sort.
+    struct Merger<'a, I, F> {
+        buffer: &'a mut [MaybeUninit<I>],
+        /// Number of consecutive wins after which to gallop.
+        min_gallop: usize,
+        compare: &'a mut F,
+    }
+
+    impl<I, F: FnMut(&I, &I) -> Ordering> Merger<'_, I, F> {
+        /// Merges the sorted runs `..mid` and `mid..` of `seq`.
+        fn merge(&mut self, seq: &mut [I], mid: usize) {
+            let compare = &mut *self.compare;
+
+            // the elements of the first run not greater than the start of the second one are in place
+            let skipped = gallop(&seq[..mid], |el| {
+                compare(el, &seq[mid]) != Ordering::Greater
+            });
+            let seq = &mut seq[skipped..];
+            let mid = mid - skipped;
+
+            if mid == 0 {
+                return;
+            }
+
+            // the elements of the second run not smaller than the end of the first one are in place
+            let kept = gallop(&seq[mid..], |el| {
+                compare(el, &seq[mid - 1]) == Ordering::Less
+            });
+            let seq = &mut seq[..mid + kept];
+
+            if mid <= kept {
+                self.merge_lo(seq, mid);
+            } else {
+                self.merge_hi(seq, mid);
+            }
+        }
+
+        /// Merges from the front, with the first run, the shorter one, in the buffer.
+        fn merge_lo(&mut self, seq: &mut [I], mid: usize) {
+            let len = seq.len();
+            let seq = seq.as_mut_ptr();
+            let buffer = self.buffer.as_mut_ptr() as *mut I;
+            let compare = &mut *self.compare;
+            let mut min_gallop = self.min_gallop;
+
+            // SAFETY: as in `merge_sort::merge`, the gap left by the first run always has the length of the rest
+            // of the buffer and it's right before the rest of the second run. Every move fills its front and the
+            // gap drops the rest of the buffer in place if a comparison panics. The slices taken for galloping
+            // don't overlap with the keys they're compared to.
+            unsafe {
+                ptr::copy_nonoverlapping(seq, buffer, mid);
+
+                let mut gap = Gap {
+                    start: buffer,
+                    end: buffer.add(mid),
+                    dest: seq,
+                };
+                let mut right = seq.add(mid);
+                let right_end = seq.add(len);
+
+                'merge: loop {
+                    let (mut left_wins, mut right_wins) = (0, 0);
+
+                    // one element at a time
+                    while left_wins < min_gallop && right_wins < min_gallop {
+                        if gap.start == gap.end || right == right_end {
+                            break 'merge;
+                        }
+
+                        if compare(&*right, &*gap.start) == Ordering::Less {
+                            ptr::copy_nonoverlapping(right, gap.dest, 1);
+                            right = right.add(1);
+                            right_wins += 1;
+                            left_wins = 0;
+                        } else {
+                            ptr::copy_nonoverlapping(gap.start, gap.dest, 1);
+                            gap.start = gap.start.add(1);
+                            left_wins += 1;
+                            right_wins = 0;
+                        }
+
+                        gap.dest = gap.dest.add(1);
+                    }
+
+                    // galloping, until both runs win fewer than `MIN_GALLOP` elements at once
+                    loop {
+                        if gap.start == gap.end || right == right_end {
+                            break 'merge;
+                        }
+
+                        let left = slice::from_raw_parts(
+                            gap.start,
+                            gap.end.offset_from(gap.start) as usize,
+                        );
+                        let left_wins =
+                            gallop(left, |el| compare(el, &*right) != Ordering::Greater);
+                        ptr::copy_nonoverlapping(gap.start, gap.dest, left_wins);
+                        gap.start = gap.start.add(left_wins);
+                        gap.dest = gap.dest.add(left_wins);
+
+                        if gap.start == gap.end {
+                            break 'merge;
+                        }
+
+                        let rest =
+                            slice::from_raw_parts(right, right_end.offset_from(right) as usize);
+                        let right_wins =
+                            gallop(rest, |el| compare(el, &*gap.start) == Ordering::Less);
+                        ptr::copy(right, gap.dest, right_wins);
+                        right = right.add(right_wins);
+                        gap.dest = gap.dest.add(right_wins);
+
+                        if left_wins < MIN_GALLOP && right_wins < MIN_GALLOP {
+                            // galloping doesn't pay off, make it harder to start again
+                            min_gallop += 1;
+                            break;
+                        }
+
+                        min_gallop = min_gallop.saturating_sub(1).max(1);
+                    }
+                }
+
+                // dropping the gap moves the rest of the first run, the second one is in place
+            }
+
+            self.min_gallop = min_gallop;
+        }
+
+        /// Merges from the back, with the second run, the shorter one, in the buffer.
+        fn merge_hi(&mut self, seq: &mut [I], mid: usize) {
+            let len = seq.len();
+            let seq = seq.as_mut_ptr();
+            let buffer = self.buffer.as_mut_ptr() as *mut I;
+            let compare = &mut *self.compare;
+            let mut min_gallop = self.min_gallop;
+
+            // SAFETY: mirrors `merge_lo`: the second run is moved to the buffer, the gap is right after the rest
+            // of the first run and it's filled from its back. `gap.dest` is the end of the first run, so dropping
+            // the gap moves the rest of the buffer right after it.
+            unsafe {
+                ptr::copy_nonoverlapping(seq.add(mid), buffer, len - mid);
+
+                let mut gap = Gap {
+                    start: buffer,
+                    end: buffer.add(len - mid),
+                    dest: seq.add(mid),
+                };
+                let mut out = seq.add(len);
+
+                'merge: loop {
+                    let (mut left_wins, mut right_wins) = (0, 0);
+
+                    // one element at a time, ties go to the second run
+                    while left_wins < min_gallop && right_wins < min_gallop {
+                        if gap.start == gap.end || gap.dest == seq {
+                            break 'merge;
+                        }
+
+                        out = out.sub(1);
+
+                        if compare(&*gap.end.sub(1), &*gap.dest.sub(1)) == Ordering::Less {
+                            gap.dest = gap.dest.sub(1);
+                            ptr::copy_nonoverlapping(gap.dest, out, 1);
+                            left_wins += 1;
+                            right_wins = 0;
+                        } else {
+                            gap.end = gap.end.sub(1);
+                            ptr::copy_nonoverlapping(gap.end, out, 1);
+                            right_wins += 1;
+                            left_wins = 0;
+                        }
+                    }
+
+                    loop {
+                        if gap.start == gap.end || gap.dest == seq {
+                            break 'merge;
+                        }
+
+                        let left = slice::from_raw_parts(seq, gap.dest.offset_from(seq) as usize);
+                        let left_wins =
+                            gallop_back(left, |el| compare(&*gap.end.sub(1), el) == Ordering::Less);
+                        out = out.sub(left_wins);
+                        gap.dest = gap.dest.sub(left_wins);
+                        ptr::copy(gap.dest, out, left_wins);
+
+                        if gap.dest == seq {
+                            break 'merge;
+                        }
+
+                        let right = slice::from_raw_parts(
+                            gap.start,
+                            gap.end.offset_from(gap.start) as usize,
+                        );
+                        let right_wins = gallop_back(right, |el| {
+                            compare(el, &*gap.dest.sub(1)) != Ordering::Less
+                        });
+                        out = out.sub(right_wins);
+                        gap.end = gap.end.sub(right_wins);
+                        ptr::copy_nonoverlapping(gap.end, out, right_wins);
+
+                        if left_wins < MIN_GALLOP && right_wins < MIN_GALLOP {
+                            min_gallop += 1;
+                            break;
+                        }
+
+                        min_gallop = min_gallop.saturating_sub(1).max(1);
+                    }
+                }
+            }
+
+            self.min_gallop = min_gallop;
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::panic::{self, AssertUnwindSafe};
+        use std::rc::Rc;
+
+        use crate::random::Rng;
+        use crate::sorting_algorithms::tests::{check_by, Entry};
+
+        /// Sequences with different kinds and lengths of runs, of pseudo-random keys below `modulo`.
+        fn inputs(len: u32, modulo: u32) -> Vec<Vec<Entry>> {
+            let mut rng = Rng::new(49);
+            let mut random = |_: u32| rng.next_u64() as u32;
+            let entries = |keys: Vec<u32>| {
+                keys.into_iter()
+                    .enumerate()
+                    .map(|(i, key)| Entry(key % modulo, i as u32))
+                    .collect()
+            };
+
+            vec![
+                entries((0..len).map(&mut random).collect()),
+                entries((0..len).collect()),
+                entries((0..len).rev().collect()),
+                // ascending and descending runs of growing lengths
+                entries(
+                    (0..len)
+                        .map(|i| {
+                            if (i as f64).sqrt() as u32 & 1 == 0 {
+                                i
+                            } else {
+                                len - i
+                            }
+                        })
+                        .collect(),
+                ),
+                // sorted with a few random elements at the end
+                entries(
+                    (0..len)
+                        .map(|i| if i + 10 < len { i } else { random(i) })
+                        .collect(),
+                ),
+                // interleaved sorted blocks, galloping through long stretches
+                entries((0..len).map(|i| (i % 4) * len + i / 4).collect()),
+            ]
+        }
+
+        #[test]
+        fn stable() {
+            for &len in &[0, 1, 2, 31, 64, 65, 1000, 5000] {
+                for &modulo in &[3, 1000, u32::MAX] {
+                    for input in inputs(len, modulo) {
+                        let mut sequence = input.clone();
+                        let mut expected = input;
+                        expected.sort();
+
+                        super::tim_sort(&mut sequence);
+
+                        assert!(expected.iter().zip(&sequence).all(|(a, b)| a.1 == b.1));
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn adaptive() {
+            let n = 10_000;
+
+            for input in [(0..n).collect::<Vec<u32>>(), (0..n).rev().collect()] {
+                let mut comparisons = 0;
+                let mut sequence = input;
+
+                super::tim_sort_by(&mut sequence, |a, b| {
+                    comparisons += 1;
+                    a.cmp(b)
+                });
+
+                assert!(sequence.windows(2).all(|w| w[0] <= w[1]));
+                assert_eq!(n as usize - 1, comparisons);
+            }
+        }
+
+        #[test]
+        fn min_run() {
+            assert_eq!(63, super::min_run_length(63));
+            assert_eq!(32, super::min_run_length(64));
+            assert_eq!(33, super::min_run_length(65));
+            assert_eq!(32, super::min_run_length(1 << 20));
+            assert!((1..5000).all(|n| n < 64 || (32..=64).contains(&super::min_run_length(n))));
+        }
+
+        #[test]
+        fn by() {
+            check_by(
+                |seq, compare| super::tim_sort_by(seq, compare),
+                |seq, key| super::tim_sort_by_key(seq, key),
+            );
+        }
+
+        #[test]
+        fn panic_safe() {
+            for limit in [10, 500, 2000, 5000] {
+                let mut rng = Rng::new(49);
+                let values: Vec<Rc<u64>> = (0..1000).map(|_| Rc::new(rng.below(331))).collect();
+                let mut sequence = values.clone();
+                let mut comparisons = 0;
+
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    super::tim_sort_by(&mut sequence, |a, b| {
+                        comparisons += 1;
+                        assert!(comparisons < limit, "comparison limit");
+                        a.cmp(b)
+                    })
+                }));
+                assert!(result.is_err());
+
+                // every element is still there once
+                assert!(values.iter().all(|value| Rc::strong_count(value) == 2));
+                drop(sequence);
+                assert!(values.iter().all(|value| Rc::strong_count(value) == 1));
+            }
+        }
+    }
+}
+
+pub mod parallel_sort {
+    use std::cmp::{Ord, Ordering};
+    use std::num::NonZeroUsize;
+    use std::thread;
+
+    use super::merge_sort::{self, merge_sort_by};
+    use super::quick_sort::{quick_sort_by, separate, PivotSelector, PivotStrategy};
+
+    /// Default length under which the sorts run sequentially.
+    const DEFAULT_CUTOFF: usize = 1 << 14;
+
+    /// How a parallel sort splits its work.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Parallelism {
+        /// Maximum number of threads working at once, including the calling one.
+        pub threads: usize,
+        /// Slices up to this length are sorted or merged sequentially.
+        pub cutoff: usize,
+    }
+
+    impl Parallelism {
+        pub fn new(threads: usize, cutoff: usize) -> Self {
+            Self { threads, cutoff }
+        }
+
+        /// Whether a slice of length `len` is worth splitting between threads.
+        fn splits(&self, len: usize) -> bool {
+            self.threads > 1 && len > self.cutoff
+        }
+
+        /// The parallelism of the two halves of a split: the first one goes to a new thread.
+        fn split(&self) -> (Self, Self) {
+            let first = self.threads / 2;
+
+            (
+                Self::new(first, self.cutoff),
+                Self::new(self.threads - first, self.cutoff),
+            )
+        }
+    }
+
+    impl Default for Parallelism {
+        /// As many threads as the available parallelism, with a cutoff of 16384 elements.
+        fn default() -> Self {
+            let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+
+            Self::new(threads, DEFAULT_CUTOFF)
+        }
+    }
+
+    /// Parallel merge sort: the two halves are sorted on different threads, then merged in parallel.
+    ///
+    /// The parallel merge splits the longer run in the middle and finds with a binary search where its middle
+    /// element goes in the other run. Rotating the two middle blocks gives two pairs of runs, with every element
+    /// of the first pair not greater than the ones of the second pair, which are merged on different threads.
+    /// The slices up to the cutoff and the pieces of the last split are sorted and merged with `merge_sort`.
+    ///
+    /// With `p` threads it takes `O(n log n / p + n log p)` time: the rotations move `O(n)` elements on each of
+    /// the `log p` levels of splits. It's stable.
+    pub fn par_merge_sort<I: Ord + Send>(seq: &mut [I], parallelism: Parallelism) {
+        par_merge_sort_by(seq, parallelism, I::cmp);
+    }
+
+    /// Same as `par_merge_sort`, with the comparator function `compare`.
+    pub fn par_merge_sort_by<I: Send, F: Fn(&I, &I) -> Ordering + Sync>(
+        seq: &mut [I],
+        parallelism: Parallelism,
+        compare: F,
+    ) {
+        merge_sort_with(seq, parallelism, &compare);
+    }
+
+    /// Same as `par_merge_sort`, sorting by the key extracted with `key`.
+    pub fn par_merge_sort_by_key<I: Send, K: Ord, F: Fn(&I) -> K + Sync>(
+        seq: &mut [I],
+        parallelism: Parallelism,
+        key: F,
+    ) {
+        merge_sort_with(seq, parallelism, &|a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    fn merge_sort_with<I: Send, F: Fn(&I, &I) -> Ordering + Sync>(
+        seq: &mut [I],
+        parallelism: Parallelism,
+        compare: &F,
+    ) {
+        if !parallelism.splits(seq.len()) {
+            merge_sort_by(seq, compare);
+            return;
+        }
+
+        let mid = seq.len() / 2;
+        let (first, second) = seq.split_at_mut(mid);
+        let (first_threads, second_threads) = parallelism.split();
+
+        thread::scope(|scope| {
+            scope.spawn(|| merge_sort_with(first, first_threads, compare));
+            merge_sort_with(second, second_threads, compare);
+        });
+
+        par_merge(seq, mid, parallelism, compare);
+    }
+
+    /// Merges the sorted runs `..mid` and `mid..` of `seq` in parallel.
+    fn par_merge<I: Send, F: Fn(&I, &I) -> Ordering + Sync>(
+        seq: &mut [I],
+        mid: usize,
+        parallelism: Parallelism,
+        compare: &F,
+    ) {
+        let len = seq.len();
+
+        if mid == 0 || mid == len {
+            return;
+        }
+
+        if !parallelism.splits(len) {
+            let mut buffer = Vec::with_capacity(mid);
+            merge_sort::merge(
+                seq,
+                mid,
+                buffer.spare_capacity_mut(),
+                &mut |a: &I, b: &I| compare(a, b),
+            );
+            return;
+        }
+
+        // `..i` and `mid..j` go before `i..mid` and `j..`: on ties the first run goes first
+        let (i, j) = if mid >= len - mid {
+            let i = mid / 2;
+            let j = mid + seq[mid..].partition_point(|el| compare(el, &seq[i]) == Ordering::Less);
+            (i, j)
+        } else {
+            let j = mid + (len - mid) / 2;
+            let i = seq[..mid].partition_point(|el| compare(el, &seq[j]) != Ordering::Greater);
+            (i, j)
+        };
+
+        seq[i..j].rotate_left(mid - i);
+
+        let (first, second) = seq.split_at_mut(i + j - mid);
+        let (first_threads, second_threads) = parallelism.split();
+
+        thread::scope(|scope| {
+            scope.spawn(|| par_merge(first, i, first_threads, compare));
+            par_merge(second, mid - i, second_threads, compare);
+        });
+    }
+
+    /// Parallel quick sort: the sequence is partitioned around the ninther, then the two sides are sorted on
+    /// different threads. The slices up to the cutoff and the sides of the last split are sorted with
+    /// `quick_sort`.
+    ///
+    /// The partitions before the splits are sequential, so with `p` threads it takes `O(n log n / p + n)` time
+    /// on average. It's unstable.
+    pub fn par_quick_sort<I: Ord + Send>(seq: &mut [I], parallelism: Parallelism) {
+        par_quick_sort_by(seq, parallelism, I::cmp);
+    }
+
+    /// Same as `par_quick_sort`, with the comparator function `compare`.
+    pub fn par_quick_sort_by<I: Send, F: Fn(&I, &I) -> Ordering + Sync>(
+        seq: &mut [I],
+        parallelism: Parallelism,
+        compare: F,
+    ) {
+        quick_sort_with(seq, parallelism, &compare);
+    }
+
+    /// Same as `par_quick_sort`, sorting by the key extracted with `key`.
+    pub fn par_quick_sort_by_key<I: Send, K: Ord, F: Fn(&I) -> K + Sync>(
+        seq: &mut [I],
+        parallelism: Parallelism,
+        key: F,
+    ) {
+        quick_sort_with(seq, parallelism, &|a: &I, b: &I| key(a).cmp(&key(b)));
+    }
+
+    fn quick_sort_with<I: Send, F: Fn(&I, &I) -> Ordering + Sync>(
+        seq: &mut [I],
+        parallelism: Parallelism,
+        compare: &F,
+    ) {
+        if !parallelism.splits(seq.len()) {
+            quick_sort_by(seq, compare);
+            return;
+        }
+
+        let mut compare_mut = |a: &I, b: &I| compare(a, b);
+        let pivot = PivotSelector::new(PivotStrategy::Ninther).select(seq, &mut compare_mut);
+        let pivot = separate(seq, pivot, &mut compare_mut);
+
+        let (first, rest) = seq.split_at_mut(pivot);
+        let second = &mut rest[1..];
+        let (first_threads, second_threads) = parallelism.split();
+
+        thread::scope(|scope| {
+            scope.spawn(|| quick_sort_with(first, first_threads, compare));
+            quick_sort_with(second, second_threads, compare);
+        });
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::Parallelism;
+        use crate::random::Rng;
+        use crate::sorting_algorithms::tests::{check_by, check_strings, Entry};
+
+        fn entries(len: u32, modulo: u32) -> Vec<Entry> {
+            let mut rng = Rng::new(50);
+            (0..len)
+                .map(|i| Entry(rng.below(modulo as u64) as u32, i))
+                .collect()
+        }
+
+        fn parallelisms() -> Vec<Parallelism> {
+            vec![
+                Parallelism::new(1, 16),
+                Parallelism::new(2, 16),
+                Parallelism::new(3, 100),
+                Parallelism::new(8, 16),
+                Parallelism::default(),
+            ]
+        }
+
+        #[test]
+        fn merge_sort() {
+            for parallelism in parallelisms() {
+                for &(len, modulo) in &[(0, 1), (1, 1), (17, 5), (1000, 7), (5000, u32::MAX)] {
+                    let mut sequence = entries(len, modulo);
+                    let mut expected = sequence.clone();
+                    expected.sort();
+
+                    super::par_merge_sort(&mut sequence, parallelism);
+
+                    assert!(expected.iter().zip(&sequence).all(|(a, b)| a.1 == b.1));
+                }
+            }
+        }
+
+        #[test]
+        fn quick_sort() {
+            for parallelism in parallelisms() {
+                for &(len, modulo) in &[(0, 1), (1, 1), (17, 5), (1000, 7), (5000, u32::MAX)] {
+                    let mut sequence: Vec<u32> = entries(len, modulo).iter().map(|e| e.0).collect();
+                    let mut expected = sequence.clone();
+                    expected.sort_unstable();
+
+                    super::par_quick_sort(&mut sequence, parallelism);
+
+                    assert_eq!(expected, sequence);
+                }
+            }
+        }
+
+        #[test]
+        fn by() {
+            let parallelism = Parallelism::new(4, 8);
+
+            check_by(
+                |seq, compare| super::par_merge_sort_by(seq, parallelism, compare),
+                |seq, key| super::par_merge_sort_by_key(seq, parallelism, key),
+            );
+            check_by(
+                |seq, compare| super::par_quick_sort_by(seq, parallelism, compare),
+                |seq, key| super::par_quick_sort_by_key(seq, parallelism, key),
+            );
+        }
+
+        #[test]
+        fn heap_data() {
+            let parallelism = Parallelism::new(4, 8);
+
+            check_strings(|seq| super::par_merge_sort(seq, parallelism));
+            check_strings(|seq| super::par_quick_sort(seq, parallelism));
+        }
     }
 }